@@ -1,12 +1,11 @@
 use clap::Parser;
 use cli::{Cli, match_commands};
-use cli::AppError;
 
-fn main() -> Result<(), AppError> {
+fn main() {
     let local_cli: Cli = cli::Cli::parse();
 
-    match_commands(&local_cli)?;
-
-    Ok(())
+    if let Err(error) = match_commands(&local_cli) {
+        eprintln!("Error: {}", error);
+        std::process::exit(1);
+    }
 }
-
@@ -0,0 +1,110 @@
+use std::path::Path;
+use crate::answers_file_name;
+use crate::constants::ANSWERS_FILE_NAME;
+use crate::render_module::Variables;
+use crate::variables_module::merge_variables;
+use crate::AppError;
+
+/// Loads the variable values stored in an answers file.
+///
+/// # Arguments
+///
+/// * `path`: &Path - The path of the answers file (a TOML table of `name = value` pairs)
+///
+/// returns: Result<Variables, AppError>
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use std::path::Path;
+/// use cli::answers_module::load_answers;
+///
+/// let answers = load_answers(Path::new("answers.toml"))?;
+/// ```
+pub fn load_answers(path: &Path) -> Result<Variables, AppError> {
+    let content = std::fs::read_to_string(path)?;
+
+    toml::from_str(&content).map_err(|error| AppError::InvalidAnswers {
+        path: path.display().to_string(),
+        error,
+    })
+}
+
+/// Returns the content of an answers file holding the given variable values,
+/// which can be passed back to `load-template --answers`.
+///
+/// # Arguments
+///
+/// * `variables`: &Variables - The values to store
+///
+/// returns: Result<String, AppError>
+pub fn answers_to_string(variables: &Variables) -> Result<String, AppError> {
+    let table = toml::Value::Table(variables.clone().into_iter().collect());
+
+    Ok(toml::to_string(&table)?)
+}
+
+/// Returns true if the given path, relative to the template root, is the answers file of a loaded project.
+/// A template saved from a loaded project holds one, it is written again from the answers of every load.
+pub fn is_answers_path(relative_path: &Path) -> bool {
+    relative_path == Path::new(answers_file_name!())
+}
+
+/// Merges the answers of a load into the answers file already in the target directory, if any,
+/// so loading a second template into a project keeps the answers of the first one. New values take priority.
+///
+/// # Arguments
+///
+/// * `path`: &Path - The path of the answers file in the target directory
+/// * `answers`: Variables - The answers of this load
+///
+/// returns: Result<Variables, AppError>
+pub fn merge_answers(path: &Path, answers: Variables) -> Result<Variables, AppError> {
+    if !path.is_file() {
+        return Ok(answers);
+    }
+
+    let mut merged = load_answers(path)?;
+    merge_variables(&mut merged, answers);
+
+    Ok(merged)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::AppError;
+    use crate::answers_module::{answers_to_string, is_answers_path, load_answers, merge_answers};
+    use crate::render_module::Variables;
+
+    #[test]
+    fn test_write_and_load_answers() -> Result<(), AppError> {
+        let path = Path::new("/tmp/templater_answers.toml");
+        let mut variables = Variables::new();
+        variables.insert("project_name".to_string(), toml::Value::from("my-app"));
+        variables.insert("docker".to_string(), toml::Value::from(true));
+        variables.insert("handlers".to_string(), toml::Value::from(vec!["users", "orders"]));
+
+        std::fs::write(path, answers_to_string(&variables)?)?;
+
+        assert_eq!(load_answers(path)?, variables);
+
+        let mut answers = Variables::new();
+        answers.insert("project_name".to_string(), toml::Value::from("other-app"));
+        answers.insert("ci".to_string(), toml::Value::from("github"));
+        let merged = merge_answers(path, answers)?;
+        assert_eq!(merged["project_name"], toml::Value::from("other-app"));
+        assert_eq!(merged["ci"], toml::Value::from("github"));
+        assert_eq!(merged["docker"], toml::Value::from(true));
+        assert_eq!(merge_answers(Path::new("/tmp/templater_no_answers.toml"), Variables::new())?, Variables::new());
+        assert!(is_answers_path(Path::new(".templater-answers.toml")));
+        assert!(!is_answers_path(Path::new("docs/.templater-answers.toml")));
+
+        std::fs::write(path, "project_name = ")?;
+        assert!(matches!(load_answers(path), Err(AppError::InvalidAnswers { .. })));
+
+        std::fs::remove_file(path)?;
+
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 use std::{io};
+use crate::validation_module::VariableViolation;
 
 #[derive(Debug, thiserror::Error)]
 pub enum AppError {
@@ -20,6 +21,87 @@ pub enum AppError {
 
     #[error("Template already exists. Please run `templateR save-template [NAME] [PATH] --overwrite` to overwrite.")]
     TemplateAlreadyExists,
+
+    #[error("The template path `{0}` renders to an empty name. Please pass a value for every variable used in its name with `--set`.")]
+    EmptyRenderedName(String),
+
+    #[error("The template path `{template_name}` renders to `{rendered_name}`, which contains a path separator. A variable in a file or directory name cannot contain `/`.")]
+    RenderedNameContainsSeparator { template_name: String, rendered_name: String },
+
+    #[error("The template path `{template_name}` renders to `{rendered_name}`, which would leave its directory. A variable in a file or directory name cannot be `.` or `..`.")]
+    RenderedNameIsRelative { template_name: String, rendered_name: String },
+
+    #[error("The template manifest `{path}` is invalid: {error}")]
+    InvalidManifest { path: String, error: toml::de::Error },
+
+    #[error("The answers file `{path}` is invalid: {error}")]
+    InvalidAnswers { path: String, error: toml::de::Error },
+
+    #[error("TOML serializer Error: {0}")]
+    TomlSerialize(#[from] toml::ser::Error),
+
+    #[error("Glob pattern Error: {0}")]
+    Glob(#[from] globset::Error),
+
+    #[error("Ignore file Error: {0}")]
+    Ignore(#[from] ignore::Error),
+
+    #[error("Could not list the files tracked by git: {0}. Please save a git repository or leave out `--git-tracked`.")]
+    GitTrackedFiles(String),
+
+    #[error("JSON Error: {0}")]
+    Json(#[from] serde_json::Error),
+
+    #[error("Template error in `{file}` at line {line}: {message}")]
+    Render { file: String, line: usize, message: String },
+
+    #[error("The template syntax in the manifest is invalid: {0}")]
+    InvalidSyntax(String),
+
+    #[error("Missing or invalid values for the template variables:\n{}\nPlease pass them with `--set KEY=VALUE` or in an `--answers` file.", .0.iter().map(|violation| format!("\t- {}", violation)).collect::<Vec<_>>().join("\n"))]
+    InvalidVariables(Vec<VariableViolation>),
+
+    #[error("The expression `{expression}` in the template manifest failed: {message}")]
+    Expression { expression: String, message: String },
+
+    #[error("The derived variables depend on each other in a cycle: {}", .0.join(" -> "))]
+    CyclicDerivedVariables(Vec<String>),
+
+    #[error("The template variable `{0}` used by `repeated_paths` is not a list. Please give it a list value.")]
+    RepeatedVariableNotList(String),
+
+    #[error("The repeated path `{0}` is loaded to the same destination for different items. Please use the item in its name.")]
+    DuplicateRepeatedPath(String),
+
+    #[error("The hook `{command}` failed with {status}.")]
+    HookFailed { command: String, status: String },
+
+    #[error("The hooks or scripts of the template `{template_name}` are new or changed since they were trusted:\n{}\nPlease review them and run `trust {template_name}`. `--no-hooks` only skips the hooks and post-load scripts, pre-load scripts always need to be trusted.", .commands.iter().map(|command| format!("\t- {}", command)).collect::<Vec<_>>().join("\n"))]
+    UntrustedHooks { template_name: String, commands: Vec<String> },
+
+    #[error("These files already exist with another content:\n{}\nPlease choose what to do with them with `--on-conflict`.", .0.iter().map(|path| format!("\t- {}", path)).collect::<Vec<_>>().join("\n"))]
+    Conflicts(Vec<String>),
+
+    #[error("The script `{script}` failed: {message}")]
+    Script { script: String, message: String },
+
+    #[error("Could not merge `{file}`: {message}. Please fix the existing file or remove it from the `merge` globs of the template.")]
+    Merge { file: String, message: String },
+
+    #[error("The marker `{marker}` was not found in `{file}`. Please add it to the file or check the `injections` of the template manifest.")]
+    MarkerNotFound { file: String, marker: String },
+
+    #[error("Could not inject into `{file}`: {message}. Please check the `injections` of the template manifest.")]
+    Injection { file: String, message: String },
+
+    #[error("`{path}` is a {existing} in the target directory but a {planned} in the template. Please move it away before loading the template.")]
+    PathKindMismatch { path: String, existing: String, planned: String },
+
+    #[error("Loading was interrupted, the target directory was restored.")]
+    Interrupted,
+
+    #[error("Loading failed ({cause}) and the target directory could not be restored: {error}. Please recover the replaced files from `{staging}`.")]
+    RollbackFailed { cause: String, error: String, staging: String },
 }
 
 // impl fmt::Display for AppError {
@@ -0,0 +1,320 @@
+use std::path::Path;
+use globset::{GlobBuilder, GlobSet, GlobSetBuilder};
+use serde::{Deserialize, Serialize};
+use crate::render_module::{value_to_string, Variables};
+use crate::template_engine_module::evaluate_expression;
+use crate::template_manifest_module::TemplateVariable;
+use crate::validation_module::validate_value;
+use crate::AppError;
+
+/// Paths of a template that are only loaded when a variable is set.
+/// A bool variable includes the paths when true; with `equals`, the variable must have that value.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct ConditionalPaths {
+    pub paths: Vec<String>,
+    pub variable: String,
+    pub equals: Option<toml::Value>,
+}
+
+impl ConditionalPaths {
+    /// Returns true if the paths should be loaded with the given variables.
+    /// An undefined variable never includes the paths.
+    pub fn holds(&self, variables: &Variables) -> bool {
+        match (variables.get(&self.variable), &self.equals) {
+            (None, _) => false,
+            (Some(value), None) => is_truthy(value),
+            (Some(value), Some(expected)) => value_to_string(value) == value_to_string(expected),
+        }
+    }
+}
+
+/// Returns true if the value of a variable counts as enabled.
+/// Strings passed on the command line such as `false`, `no`, `off` or `0` count as disabled.
+///
+/// # Arguments
+///
+/// * `value`: &toml::Value - The value of a template variable
+///
+/// returns: bool
+pub fn is_truthy(value: &toml::Value) -> bool {
+    match value {
+        toml::Value::Boolean(value) => *value,
+        toml::Value::Integer(value) => *value != 0,
+        toml::Value::Float(value) => *value != 0.0,
+        toml::Value::String(value) => !matches!(value.trim().to_lowercase().as_str(), "" | "false" | "no" | "off" | "0"),
+        toml::Value::Array(value) => !value.is_empty(),
+        toml::Value::Table(value) => !value.is_empty(),
+        toml::Value::Datetime(_) => true,
+    }
+}
+
+/// Builds a matcher for the given glob patterns. `*` does not match `/`, use `**` to match across directories.
+///
+/// # Arguments
+///
+/// * `patterns`: I - The glob patterns, relative to the template root
+///
+/// returns: Result<GlobSet, AppError>
+pub fn build_glob_set<I, S>(patterns: I) -> Result<GlobSet, AppError>
+where
+    I: IntoIterator<Item = S>,
+    S: AsRef<str>,
+{
+    let mut builder = GlobSetBuilder::new();
+
+    for pattern in patterns {
+        builder.add(GlobBuilder::new(pattern.as_ref()).literal_separator(true).build()?);
+    }
+
+    Ok(builder.build()?)
+}
+
+/// Builds a matcher for every path that must be left out of the loaded template,
+/// because the condition tied to it does not hold or because it is always left out.
+///
+/// # Arguments
+///
+/// * `conditional_paths`: &[ConditionalPaths] - The conditional paths declared by the template manifest
+/// * `always_excluded`: &[String] - Globs of paths that are never loaded, such as partials
+/// * `variables`: &Variables - The resolved variables
+///
+/// returns: Result<GlobSet, AppError>
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use std::path::Path;
+/// use cli::conditions_module::excluded_paths;
+///
+/// let excluded = excluded_paths(&manifest.conditional_paths, &manifest.partials, &variables)?;
+///
+/// if excluded.is_match(Path::new("docker")) {
+///     println!("The docker folder is skipped.");
+/// }
+/// ```
+pub fn excluded_paths(
+    conditional_paths: &[ConditionalPaths],
+    always_excluded: &[String],
+    variables: &Variables,
+) -> Result<GlobSet, AppError> {
+    build_glob_set(
+        conditional_paths
+            .iter()
+            .filter(|conditional| !conditional.holds(variables))
+            .flat_map(|conditional| conditional.paths.iter())
+            .chain(always_excluded),
+    )
+}
+
+/// Returns true if the given path, relative to the template root, must be left out.
+pub fn is_excluded(excluded: &GlobSet, relative_path: &Path) -> bool {
+    excluded.is_match(relative_path)
+}
+
+/// Returns true if the variable must be asked, that is when it has no `when` condition or when the condition holds.
+///
+/// # Arguments
+///
+/// * `variable`: &TemplateVariable - The declaration of the variable
+/// * `variables`: &Variables - The values known so far
+///
+/// returns: Result<bool, AppError>
+pub fn is_asked(variable: &TemplateVariable, variables: &Variables) -> Result<bool, AppError> {
+    match &variable.when {
+        None => Ok(true),
+        Some(condition) => Ok(evaluate_expression(condition, variables)?.is_true()),
+    }
+}
+
+/// Gives a variable that is not asked its default value, or leaves it undefined when it has none.
+///
+/// # Arguments
+///
+/// * `variable`: &TemplateVariable - The declaration of the skipped variable
+/// * `variables`: &mut Variables - The values to change
+pub fn skip_variable(variable: &TemplateVariable, variables: &mut Variables) {
+    match &variable.default {
+        None => variables.remove(&variable.name),
+        Some(default) => variables.insert(variable.name.clone(), default.clone()),
+    };
+}
+
+/// Returns the values that are not declared by the manifest, such as the built-in variables.
+/// The `when` conditions start from them and see every declared variable only once it is reached.
+///
+/// # Arguments
+///
+/// * `declared`: &[TemplateVariable] - The variables declared by the template manifest
+/// * `variables`: &Variables - The resolved values
+///
+/// returns: Variables
+pub fn undeclared_values(declared: &[TemplateVariable], variables: &Variables) -> Variables {
+    variables
+        .iter()
+        .filter(|(name, _)| !declared.iter().any(|variable| &variable.name == *name))
+        .map(|(name, value)| (name.clone(), value.clone()))
+        .collect()
+}
+
+/// Evaluates the `when` condition of every declared variable, in declaration order, so a condition sees the values
+/// of the variables before it. A skipped variable gets its default value or stays undefined, even if a value was given.
+///
+/// # Arguments
+///
+/// * `declared`: &[TemplateVariable] - The variables declared by the template manifest
+/// * `variables`: &mut Variables - The resolved values, the skipped variables are reset
+///
+/// returns: Result<Vec<TemplateVariable>, AppError> - The declarations of the variables that are asked
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use cli::conditions_module::active_variables;
+/// use cli::validation_module::validate_variables;
+///
+/// let active = active_variables(&manifest.variables, &mut variables)?;
+/// validate_variables(&active, &mut variables)?;
+/// ```
+pub fn active_variables(declared: &[TemplateVariable], variables: &mut Variables) -> Result<Vec<TemplateVariable>, AppError> {
+    let mut known = undeclared_values(declared, variables);
+    let mut active = Vec::new();
+
+    for variable in declared {
+        if !is_asked(variable, &known)? {
+            skip_variable(variable, variables);
+            if let Some(value) = variables.get(&variable.name) {
+                known.insert(variable.name.clone(), value.clone());
+            }
+            continue;
+        }
+
+        // Conditions compare typed values, e.g. `docker=false` given on the command line is a bool
+        if let Some(value) = variables.get(&variable.name) {
+            known.insert(variable.name.clone(), validate_value(variable, value).unwrap_or_else(|_| value.clone()));
+        }
+        active.push(variable.clone());
+    }
+
+    Ok(active)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::AppError;
+    use crate::conditions_module::{active_variables, excluded_paths, is_excluded, is_truthy, ConditionalPaths};
+    use crate::render_module::Variables;
+    use crate::template_manifest_module::{TemplateVariable, VariableType};
+
+    #[test]
+    fn test_is_truthy() {
+        assert!(is_truthy(&toml::Value::from(true)));
+        assert!(is_truthy(&toml::Value::from("yes")));
+        assert!(!is_truthy(&toml::Value::from("False")));
+        assert!(!is_truthy(&toml::Value::from("0")));
+        assert!(!is_truthy(&toml::Value::from(0)));
+        assert!(!is_truthy(&toml::Value::Array(Vec::new())));
+    }
+
+    #[test]
+    fn test_excluded_paths() -> Result<(), AppError> {
+        let conditional_paths = vec![
+            ConditionalPaths {
+                paths: vec!["docker".to_string(), "Dockerfile".to_string()],
+                variable: "docker".to_string(),
+                equals: None,
+            },
+            ConditionalPaths {
+                paths: vec![".github".to_string()],
+                variable: "ci".to_string(),
+                equals: Some(toml::Value::from("github")),
+            },
+            ConditionalPaths {
+                paths: vec![".gitlab-ci.yml".to_string()],
+                variable: "ci".to_string(),
+                equals: Some(toml::Value::from("gitlab")),
+            },
+            ConditionalPaths {
+                paths: vec!["benches/*.rs".to_string()],
+                variable: "benches".to_string(),
+                equals: None,
+            },
+        ];
+
+        let mut variables = Variables::new();
+        variables.insert("docker".to_string(), toml::Value::from("false"));
+        variables.insert("ci".to_string(), toml::Value::from("github"));
+
+        let excluded = excluded_paths(&conditional_paths, &["_partials".to_string()], &variables)?;
+
+        assert!(is_excluded(&excluded, Path::new("docker")));
+        assert!(is_excluded(&excluded, Path::new("Dockerfile")));
+        assert!(!is_excluded(&excluded, Path::new(".github")));
+        assert!(is_excluded(&excluded, Path::new(".gitlab-ci.yml")));
+        assert!(is_excluded(&excluded, Path::new("benches/main.rs")));
+        assert!(!is_excluded(&excluded, Path::new("benches/nested/main.rs")));
+        assert!(!is_excluded(&excluded, Path::new("src/main.rs")));
+        assert!(is_excluded(&excluded, Path::new("_partials")));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_active_variables() -> Result<(), AppError> {
+        let declared = vec![
+            TemplateVariable {
+                name: "database".to_string(),
+                variable_type: VariableType::Bool,
+                ..Default::default()
+            },
+            TemplateVariable {
+                name: "db_kind".to_string(),
+                default: Some(toml::Value::from("postgres")),
+                when: Some("database".to_string()),
+                ..Default::default()
+            },
+            TemplateVariable {
+                name: "db_url".to_string(),
+                when: Some("database and db_kind != 'sqlite'".to_string()),
+                ..Default::default()
+            },
+        ];
+
+        let mut variables = Variables::new();
+        variables.insert("database".to_string(), toml::Value::from("false"));
+        variables.insert("db_kind".to_string(), toml::Value::from("sqlite"));
+        variables.insert("db_url".to_string(), toml::Value::from("postgres://localhost"));
+
+        let active = active_variables(&declared, &mut variables)?;
+
+        assert_eq!(active.len(), 1);
+        assert_eq!(variables["db_kind"], toml::Value::from("postgres"));
+        assert!(!variables.contains_key("db_url"));
+
+        variables.insert("database".to_string(), toml::Value::from(true));
+        variables.insert("db_kind".to_string(), toml::Value::from("sqlite"));
+
+        let active: Vec<String> = active_variables(&declared, &mut variables)?.into_iter().map(|variable| variable.name).collect();
+
+        assert_eq!(active, vec!["database", "db_kind"]);
+
+        // A condition cannot read a variable declared after it, even when a value was given for it
+        let forward = vec![
+            TemplateVariable {
+                name: "db_url".to_string(),
+                when: Some("database".to_string()),
+                ..Default::default()
+            },
+            TemplateVariable {
+                name: "database".to_string(),
+                variable_type: VariableType::Bool,
+                ..Default::default()
+            },
+        ];
+        let active: Vec<String> = active_variables(&forward, &mut variables)?.into_iter().map(|variable| variable.name).collect();
+        assert_eq!(active, vec!["database"]);
+
+        Ok(())
+    }
+}
@@ -0,0 +1,200 @@
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use clap::ValueEnum;
+use crate::plan_module::{LoadPlan, PlanAction};
+use crate::AppError;
+
+/// What `load-template` does with a file that already exists in the target directory with another content.
+#[derive(ValueEnum, Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the existing file
+    Skip,
+    /// Replace the existing file
+    Overwrite,
+    /// Stop before anything is written
+    #[default]
+    Fail,
+    /// Ask for every conflicting file, or stop like `fail` without a terminal or with `--no-input`
+    Prompt,
+    /// Move the existing file to `<name>.bak` and replace it
+    Backup,
+}
+
+/// Applies the conflict policy to every conflicting file of the plan.
+///
+/// # Arguments
+///
+/// * `plan`: &mut LoadPlan - The plan, with its actions resolved against the target directory
+/// * `policy`: ConflictPolicy - What to do with the conflicting files
+/// * `target`: &Path - The directory the template is loaded into
+/// * `input`: R - Where the answers of the `prompt` policy are read from
+/// * `output`: W - Where the questions of the `prompt` policy are written to
+///
+/// returns: Result<(), AppError>
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use cli::conflict_module::{resolve_conflicts, ConflictPolicy};
+///
+/// plan.resolve_actions(&target)?;
+/// resolve_conflicts(&mut plan, ConflictPolicy::Backup, &target, std::io::stdin().lock(), std::io::stderr())?;
+/// ```
+pub fn resolve_conflicts<R: BufRead, W: Write>(
+    plan: &mut LoadPlan,
+    policy: ConflictPolicy,
+    target: &Path,
+    mut input: R,
+    mut output: W,
+) -> Result<(), AppError> {
+    if policy == ConflictPolicy::Fail && plan.conflicts().next().is_some() {
+        return Err(AppError::Conflicts(plan.conflicts().map(|file| file.path.display().to_string()).collect()));
+    }
+
+    for file in plan.files.iter_mut().filter(|file| file.conflict) {
+        let policy = match policy {
+            ConflictPolicy::Prompt => ask_policy(&file.path, &mut input, &mut output)?,
+            policy => policy,
+        };
+
+        file.action = match policy {
+            ConflictPolicy::Skip => PlanAction::Skip,
+            ConflictPolicy::Backup => PlanAction::Backup,
+            _ => PlanAction::Overwrite,
+        };
+        file.backup = match policy {
+            ConflictPolicy::Backup => Some(backup_path(target, &file.path)),
+            _ => None,
+        };
+    }
+
+    Ok(())
+}
+
+/// Asks what to do with one conflicting file until a valid answer is given.
+fn ask_policy<R: BufRead, W: Write>(path: &Path, input: &mut R, output: &mut W) -> Result<ConflictPolicy, AppError> {
+    loop {
+        write!(output, "`{}` already exists with another content. Overwrite, skip or backup? [o/s/b]: ", path.display())?;
+        output.flush()?;
+
+        let mut answer = String::new();
+        if input.read_line(&mut answer)? == 0 {
+            return Err(AppError::Io(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                format!("No answer was given for the conflicting file `{}`.", path.display()),
+            )));
+        }
+
+        match answer.trim().to_lowercase().as_str() {
+            "o" | "overwrite" => return Ok(ConflictPolicy::Overwrite),
+            "s" | "skip" => return Ok(ConflictPolicy::Skip),
+            "b" | "backup" => return Ok(ConflictPolicy::Backup),
+            _ => writeln!(output, "Please answer `o`, `s` or `b`.")?,
+        }
+    }
+}
+
+/// Returns a free backup path for a file, `<name>.bak` or `<name>.bak.<n>` when it is taken.
+fn backup_path(target: &Path, path: &Path) -> PathBuf {
+    let mut name = path.as_os_str().to_os_string();
+    name.push(".bak");
+    let mut backup = PathBuf::from(&name);
+
+    let mut number = 1;
+    while target.join(&backup).exists() {
+        let mut numbered = name.clone();
+        numbered.push(format!(".{}", number));
+        backup = PathBuf::from(numbered);
+        number += 1;
+    }
+
+    backup
+}
+
+/// Prints what happened to every conflicting file of a written plan.
+///
+/// # Arguments
+///
+/// * `plan`: &LoadPlan - The written plan
+/// * `output`: W - Where the summary is written
+///
+/// returns: Result<(), AppError>
+pub fn print_conflicts<W: Write>(plan: &LoadPlan, mut output: W) -> Result<(), AppError> {
+    if plan.conflicts().next().is_none() {
+        return Ok(());
+    }
+
+    writeln!(output, "Conflicting files:")?;
+    for file in plan.conflicts() {
+        match (file.action, &file.backup) {
+            (PlanAction::Skip | PlanAction::Inject, _) => writeln!(output, "\tkept        {}", file.path.display())?,
+            (_, Some(backup)) => writeln!(output, "\tbacked up   {} to {}", file.path.display(), backup.display())?,
+            (_, None) => writeln!(output, "\toverwritten {}", file.path.display())?,
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+    use crate::AppError;
+    use crate::conflict_module::{print_conflicts, resolve_conflicts, ConflictPolicy};
+    use crate::plan_module::{LoadPlan, PlanAction};
+
+    fn test_plan(target: &Path) -> Result<LoadPlan, AppError> {
+        let mut plan = LoadPlan::default();
+        plan.add_file(PathBuf::from("Cargo.toml"), None, b"name = \"new\"".to_vec(), None);
+        plan.add_file(PathBuf::from("README.md"), None, b"# new".to_vec(), None);
+        plan.add_file(PathBuf::from("main.rs"), None, b"fn main() {}".to_vec(), None);
+        plan.resolve_actions(target)?;
+        Ok(plan)
+    }
+
+    #[test]
+    fn test_resolve_conflicts() -> Result<(), AppError> {
+        let target = Path::new("/tmp/templater_conflict_target/");
+        std::fs::create_dir_all(target)?;
+        std::fs::write(target.join("Cargo.toml"), "name = \"old\"")?;
+        std::fs::write(target.join("README.md"), "# old")?;
+        std::fs::write(target.join("README.md.bak"), "# older")?;
+
+        let mut plan = test_plan(target)?;
+        match resolve_conflicts(&mut plan, ConflictPolicy::Fail, target, "".as_bytes(), Vec::new()) {
+            Err(AppError::Conflicts(paths)) => assert_eq!(paths, vec!["Cargo.toml", "README.md"]),
+            other => panic!("Expected conflicts, got {:?}", other),
+        }
+
+        let mut output = Vec::new();
+        resolve_conflicts(&mut plan, ConflictPolicy::Prompt, target, "x\ns\nb\n".as_bytes(), &mut output)?;
+        assert!(String::from_utf8(output).unwrap().contains("Please answer"));
+
+        let actions: Vec<PlanAction> = plan.files.iter().map(|file| file.action).collect();
+        assert_eq!(actions, vec![PlanAction::Skip, PlanAction::Backup, PlanAction::Create]);
+        assert_eq!(plan.files[1].backup, Some(PathBuf::from("README.md.bak.1")));
+
+        plan.write(target)?;
+
+        assert_eq!(std::fs::read_to_string(target.join("Cargo.toml"))?, "name = \"old\"");
+        assert_eq!(std::fs::read_to_string(target.join("README.md"))?, "# new");
+        assert_eq!(std::fs::read_to_string(target.join("README.md.bak.1"))?, "# old");
+        assert_eq!(std::fs::read_to_string(target.join("README.md.bak"))?, "# older");
+
+        let mut summary = Vec::new();
+        print_conflicts(&plan, &mut summary)?;
+        let summary = String::from_utf8(summary).unwrap();
+        assert!(summary.contains("kept        Cargo.toml"));
+        assert!(summary.contains("backed up   README.md to README.md.bak.1"));
+        assert!(!summary.contains("main.rs"));
+
+        let mut plan = test_plan(target)?;
+        resolve_conflicts(&mut plan, ConflictPolicy::Overwrite, target, "".as_bytes(), Vec::new())?;
+        plan.write(target)?;
+        assert_eq!(std::fs::read_to_string(target.join("Cargo.toml"))?, "name = \"new\"");
+
+        std::fs::remove_dir_all(target)?;
+
+        Ok(())
+    }
+}
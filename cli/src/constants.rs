@@ -5,6 +5,10 @@ pub static APP_VERSION_STRING: &str = "1.0";
 pub static APP_AUTHOR: &str = "andreishark";
 pub static APP_ABOUT: &str = "A simple templating tool.";
 pub static TEMPLATE_FOLDER_NAME: &str = "templates";
+pub static TEMPLATE_MANIFEST_NAME: &str = "template.toml";
+pub static ANSWERS_FILE_NAME: &str = ".templater-answers.toml";
+pub static TRUST_STORE_NAME: &str = "trust";
+pub static TEMPLATE_IGNORE_NAME: &str = ".templateignore";
 
 #[macro_export]
 macro_rules! app_name {
@@ -56,6 +60,34 @@ macro_rules! template_folder_name {
     };
 }
 
+#[macro_export]
+macro_rules! template_manifest_name {
+    () => {
+        TEMPLATE_MANIFEST_NAME
+    };
+}
+
+#[macro_export]
+macro_rules! answers_file_name {
+    () => {
+        ANSWERS_FILE_NAME
+    };
+}
+
+#[macro_export]
+macro_rules! trust_store_name {
+    () => {
+        TRUST_STORE_NAME
+    };
+}
+
+#[macro_export]
+macro_rules! template_ignore_name {
+    () => {
+        TEMPLATE_IGNORE_NAME
+    };
+}
+
 #[macro_export]
 macro_rules! template_path {
     () => {
@@ -0,0 +1,136 @@
+use std::collections::BTreeMap;
+use crate::render_module::Variables;
+use crate::template_engine_module::{evaluate_expression, expression_variables};
+use crate::AppError;
+
+/// Computes the derived variables declared in the `[derived]` table of the manifest, in dependency order,
+/// so a derived variable can use other derived variables.
+///
+/// # Arguments
+///
+/// * `derived`: &BTreeMap<String, String> - The name and expression of every derived variable
+/// * `variables`: &mut Variables - The resolved variables, extended with the derived values
+///
+/// returns: Result<(), AppError>
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use std::collections::BTreeMap;
+/// use cli::derived_module::compute_derived;
+///
+/// let mut derived = BTreeMap::new();
+/// derived.insert("lib_name".to_string(), "crate_name | snake_case".to_string());
+///
+/// compute_derived(&derived, &mut variables)?;
+/// ```
+pub fn compute_derived(derived: &BTreeMap<String, String>, variables: &mut Variables) -> Result<(), AppError> {
+    for name in derived_order(derived)? {
+        let expression = &derived[&name];
+        let value = evaluate_expression(expression, variables)?;
+
+        let value = Some(value)
+            .filter(|value| !value.is_none() && !value.is_undefined())
+            .and_then(|value| toml::Value::try_from(&value).ok())
+            .ok_or_else(|| AppError::Expression {
+                expression: expression.clone(),
+                message: format!("the derived variable `{}` has no value", name),
+            })?;
+
+        variables.insert(name, value);
+    }
+
+    Ok(())
+}
+
+/// Orders the derived variables so every variable comes after the derived variables it reads.
+fn derived_order(derived: &BTreeMap<String, String>) -> Result<Vec<String>, AppError> {
+    let mut dependencies = BTreeMap::new();
+    for (name, expression) in derived {
+        let mut used: Vec<String> = expression_variables(expression)?
+            .into_iter()
+            .filter(|used| derived.contains_key(used))
+            .collect();
+        used.sort();
+        dependencies.insert(name.clone(), used);
+    }
+
+    let mut order = Vec::new();
+    let mut path = Vec::new();
+    for name in dependencies.keys() {
+        visit(name, &dependencies, &mut path, &mut order)?;
+    }
+
+    Ok(order)
+}
+
+fn visit(
+    name: &String,
+    dependencies: &BTreeMap<String, Vec<String>>,
+    path: &mut Vec<String>,
+    order: &mut Vec<String>,
+) -> Result<(), AppError> {
+    if order.contains(name) {
+        return Ok(());
+    }
+
+    if let Some(start) = path.iter().position(|visited| visited == name) {
+        let mut cycle = path[start..].to_vec();
+        cycle.push(name.clone());
+        return Err(AppError::CyclicDerivedVariables(cycle));
+    }
+
+    path.push(name.clone());
+    for dependency in &dependencies[name] {
+        visit(dependency, dependencies, path, order)?;
+    }
+    path.pop();
+
+    order.push(name.clone());
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use crate::AppError;
+    use crate::derived_module::compute_derived;
+    use crate::render_module::Variables;
+
+    fn derived(definitions: &[(&str, &str)]) -> BTreeMap<String, String> {
+        definitions.iter().map(|(name, expression)| (name.to_string(), expression.to_string())).collect()
+    }
+
+    #[test]
+    fn test_compute_derived() -> Result<(), AppError> {
+        let mut variables = Variables::new();
+        variables.insert("crate_name".to_string(), toml::Value::from("my-app"));
+        variables.insert("backend".to_string(), toml::Value::from("none"));
+
+        let definitions = derived(&[
+            ("binary_name", "lib_name | kebab_case"),
+            ("lib_name", "crate_name | snake_case"),
+            ("has_db", "backend != 'none'"),
+        ]);
+
+        compute_derived(&definitions, &mut variables)?;
+
+        assert_eq!(variables["lib_name"], toml::Value::from("my_app"));
+        assert_eq!(variables["binary_name"], toml::Value::from("my-app"));
+        assert_eq!(variables["has_db"], toml::Value::from(false));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_compute_derived_cycle() {
+        let mut variables = Variables::new();
+        let definitions = derived(&[("a", "b ~ '1'"), ("b", "c ~ '2'"), ("c", "a ~ '3'"), ("d", "'4'")]);
+
+        match compute_derived(&definitions, &mut variables) {
+            Err(AppError::CyclicDerivedVariables(cycle)) => assert_eq!(cycle, vec!["a", "b", "c", "a"]),
+            other => panic!("Expected a cycle, got {:?}", other),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use heck::{ToKebabCase, ToLowerCamelCase, ToShoutySnakeCase, ToSnakeCase, ToTitleCase, ToUpperCamelCase};
+use minijinja::Environment;
+
+/// Converts a name to `snake_case`, e.g. `My App` becomes `my_app`.
+pub fn snake_case(value: &str) -> String {
+    value.to_snake_case()
+}
+
+/// Converts a name to `PascalCase`, e.g. `my-app` becomes `MyApp`.
+pub fn pascal_case(value: &str) -> String {
+    value.to_upper_camel_case()
+}
+
+/// Converts a name to `camelCase`, e.g. `my-app` becomes `myApp`.
+pub fn camel_case(value: &str) -> String {
+    value.to_lower_camel_case()
+}
+
+/// Converts a name to `kebab-case`, e.g. `MyApp` becomes `my-app`.
+pub fn kebab_case(value: &str) -> String {
+    value.to_kebab_case()
+}
+
+/// Converts a name to `SCREAMING_SNAKE_CASE`, e.g. `my-app` becomes `MY_APP`.
+pub fn screaming_snake_case(value: &str) -> String {
+    value.to_shouty_snake_case()
+}
+
+/// Converts a name to `Title Case`, e.g. `my_app` becomes `My App`.
+pub fn title_case(value: &str) -> String {
+    value.to_title_case()
+}
+
+/// Returns the English plural of a noun, e.g. `handler` becomes `handlers` and `category` becomes `categories`.
+/// Only the regular rules are applied.
+pub fn pluralize(value: &str) -> String {
+    let lowercase = value.to_lowercase();
+    let ends_with_consonant_y = lowercase.ends_with('y')
+        && !lowercase[..lowercase.len() - 1].ends_with(['a', 'e', 'i', 'o', 'u']);
+
+    if value.is_empty() {
+        String::new()
+    } else if ends_with_consonant_y {
+        format!("{}ies", &value[..value.len() - 1])
+    } else if ["s", "x", "z", "ch", "sh"].iter().any(|suffix| lowercase.ends_with(suffix)) {
+        format!("{}es", value)
+    } else {
+        format!("{}s", value)
+    }
+}
+
+/// Registers the string filters of templateR on a template environment,
+/// next to the built-in ones such as `trim`, `replace`, `lower` and `upper`.
+///
+/// # Arguments
+///
+/// * `environment`: &mut Environment - The environment the filters are added to
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use cli::filters_module::add_filters;
+///
+/// let mut environment = minijinja::Environment::new();
+/// add_filters(&mut environment);
+///
+/// assert_eq!(environment.render_str("{{ 'my app' | pascal_case }}", ())?, "MyApp");
+/// ```
+pub fn add_filters(environment: &mut Environment) {
+    environment.add_filter("snake_case", |value: &str| snake_case(value));
+    environment.add_filter("pascal_case", |value: &str| pascal_case(value));
+    environment.add_filter("camel_case", |value: &str| camel_case(value));
+    environment.add_filter("kebab_case", |value: &str| kebab_case(value));
+    environment.add_filter("screaming_snake_case", |value: &str| screaming_snake_case(value));
+    environment.add_filter("title_case", |value: &str| title_case(value));
+    environment.add_filter("pluralize", |value: &str| pluralize(value));
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::filters_module::{add_filters, camel_case, kebab_case, pascal_case, pluralize, screaming_snake_case, snake_case, title_case};
+
+    #[test]
+    fn test_case_conversions() {
+        assert_eq!(snake_case("My Web-App"), "my_web_app");
+        assert_eq!(pascal_case("my-web_app"), "MyWebApp");
+        assert_eq!(camel_case("my-web_app"), "myWebApp");
+        assert_eq!(kebab_case("MyWebApp"), "my-web-app");
+        assert_eq!(screaming_snake_case("my-web-app"), "MY_WEB_APP");
+        assert_eq!(title_case("my_web_app"), "My Web App");
+    }
+
+    #[test]
+    fn test_pluralize() {
+        assert_eq!(pluralize("handler"), "handlers");
+        assert_eq!(pluralize("category"), "categories");
+        assert_eq!(pluralize("key"), "keys");
+        assert_eq!(pluralize("box"), "boxes");
+        assert_eq!(pluralize("Match"), "Matches");
+        assert_eq!(pluralize(""), "");
+    }
+
+    #[test]
+    fn test_add_filters() {
+        let mut environment = minijinja::Environment::new();
+        add_filters(&mut environment);
+
+        let rendered = environment
+            .render_str(
+                "{{ name | snake_case }} {{ name | pascal_case }} {{ name | kebab_case }} {{ name | screaming_snake_case }} \
+                {{ 'order' | pluralize }} {{ '  x ' | trim }} {{ name | replace('-', '.') | upper }}",
+                minijinja::context! { name => "my-app" },
+            )
+            .unwrap();
+
+        assert_eq!(rendered, "my_app MyApp my-app MY_APP orders x MY.APP");
+    }
+}
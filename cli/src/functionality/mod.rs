@@ -1,29 +1,172 @@
-use std::path::{Path};
-use crate::{app_name, config_name};
+use std::io::IsTerminal;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use globset::GlobSet;
+use crate::{answers_file_name, app_name, config_name};
 use crate::AppError;
-use crate::{InitPushArgs, LoadTemplateArgs, SaveTemplateArgs};
+use crate::{InitPushArgs, LoadTemplateArgs, SaveTemplateArgs, TrustArgs};
 use crate::template_config_module::{check_config, create_default_config, create_manual_config, delete_config_parent, InitialConfig};
-use crate::constants::{APP_NAME, CONFIG_NAME};
+use crate::constants::{ANSWERS_FILE_NAME, APP_NAME, CONFIG_NAME};
+use crate::conditions_module::{active_variables, build_glob_set, excluded_paths, is_excluded};
+use crate::answers_module::{answers_to_string, is_answers_path, load_answers, merge_answers};
+use crate::derived_module::compute_derived;
+use crate::hooks_module::{hook_environment, run_hooks};
+use crate::ignore_module::{saved_paths, GitFilter, SaveFilter};
+use crate::plan_module::LoadPlan;
+use crate::conflict_module::{print_conflicts, resolve_conflicts, ConflictPolicy};
+use crate::merge_module::{merge_files, print_merges};
+use crate::inject_module::{apply_injections, print_injections};
+use crate::transaction_module::watch_interrupts;
+use crate::trust_module::{check_trusted, code_hash, load_trust_store, store_trust_store, trust_store_path, TemplateCode};
+use crate::validation_module::validate_variables;
+use crate::variables_module::{builtin_variables, merge_variables, set_variable};
+use crate::prompt_module::prompt_variables;
+use crate::scripts_module::ScriptRunner;
+use crate::render_module::{render_file_content, render_file_name, value_to_string, Variables};
+use crate::repeat_module::{repetitions, Repetition};
+use crate::template_engine_module::TemplateEngine;
+use crate::template_manifest_module::{is_manifest_path, load_manifest, TemplateManifest};
+
+/// Copies the source directory into the destination, leaving out the paths the filter rejects.
+fn copy_to_dest(source: &Path, dest: &Path, filter: &SaveFilter) -> Result<(), AppError> {
+    for relative_path in saved_paths(source, filter)? {
+        let item_path = source.join(&relative_path);
+        let destination_path = dest.join(&relative_path);
 
-fn copy_to_dest(source: &Path, dest: &Path) -> Result<(), AppError> {
+        if item_path.is_dir() {
+            std::fs::create_dir_all(destination_path)?;
+        } else {
+            std::fs::copy(item_path, destination_path)?;
+        }
+    }
 
-    let iterated_paths = std::fs::read_dir(source)?;
+    Ok(())
+}
+
+/// Globs of template paths that are not loaded like the rest of the files.
+struct PathRules {
+    /// Paths left out of the loaded project
+    excluded: GlobSet,
+    /// Paths whose content is copied without rendering
+    verbatim: GlobSet,
+    /// Paths loaded once for every item of a list variable
+    repeated: Vec<Repetition>,
+}
+
+/// Renders a stored template into a plan of the directories and files to write,
+/// rendering every file and directory name and every text file with the template engine.
+/// Binary files and `verbatim` files are kept byte-for-byte, file permissions are kept
+/// and the template manifest, the answers file as well as the `excluded` paths are left out.
+/// A `repeated` path is rendered once per item, each time with the item available to the engine.
+fn render_to_plan(source: &Path, engine: &TemplateEngine, rules: &PathRules) -> Result<LoadPlan, AppError> {
+    let mut plan = LoadPlan::default();
+    render_dir_to_plan(source, Path::new(""), Path::new(""), engine, rules, &mut plan)?;
+    Ok(plan)
+}
+
+fn render_dir_to_plan(
+    root: &Path,
+    relative: &Path,
+    dest: &Path,
+    engine: &TemplateEngine,
+    rules: &PathRules,
+    plan: &mut LoadPlan,
+) -> Result<(), AppError> {
+    let mut iterated_paths = std::fs::read_dir(root.join(relative))?.collect::<Result<Vec<_>, _>>()?;
+    iterated_paths.sort_by_key(|item| item.file_name());
 
     for item in iterated_paths {
-        let item = item?;
-        let item_path = std::fs::canonicalize(item.path())?;
-        let destination_path = dest.join(item_path.file_name().unwrap());
+        let relative_path = relative.join(item.file_name());
 
-        if item_path.is_dir() {
-            copy_dir::copy_dir(item_path, destination_path)?;
-        } else {
-            std::fs::copy(item_path, destination_path)?;
+        if is_manifest_path(&relative_path) || is_answers_path(&relative_path) || is_excluded(&rules.excluded, &relative_path) {
+            continue;
+        }
+
+        match rules.repeated.iter().find(|repetition| repetition.matches(&relative_path)) {
+            None => {
+                render_path_to_plan(root, &relative_path, dest, engine, rules, plan)?;
+            }
+            Some(repetition) => {
+                let mut destinations = HashSet::new();
+
+                for value in &repetition.items {
+                    let engine = engine.with_variable(&repetition.item, value);
+                    let destination_path = render_path_to_plan(root, &relative_path, dest, &engine, rules, plan)?;
+
+                    if !destinations.insert(destination_path) {
+                        return Err(AppError::DuplicateRepeatedPath(relative_path.display().to_string()));
+                    }
+                }
+            }
         }
     }
 
     Ok(())
 }
 
+/// Renders one file or directory of the template into the plan and returns its path relative to the target.
+fn render_path_to_plan(
+    root: &Path,
+    relative_path: &Path,
+    dest: &Path,
+    engine: &TemplateEngine,
+    rules: &PathRules,
+    plan: &mut LoadPlan,
+) -> Result<PathBuf, AppError> {
+    let item_path = std::fs::canonicalize(root.join(relative_path))?;
+    let destination_path = dest.join(render_file_name(engine, relative_path.file_name().unwrap())?);
+
+    if item_path.is_dir() {
+        plan.add_directory(destination_path.clone());
+        render_dir_to_plan(root, relative_path, &destination_path, engine, rules, plan)?;
+    } else {
+        let content = std::fs::read(&item_path)?;
+        let content = if rules.verbatim.is_match(relative_path) {
+            content
+        } else {
+            render_file_content(engine, &relative_path.to_string_lossy(), content)?
+        };
+        let permissions = std::fs::metadata(&item_path)?.permissions();
+
+        plan.add_file(destination_path.clone(), Some(relative_path.to_path_buf()), content, Some(permissions));
+    }
+
+    Ok(destination_path)
+}
+
+/// Returns true if `load-template` may ask questions: input is not switched off and comes from a terminal.
+fn is_interactive(args: &LoadTemplateArgs) -> bool {
+    !args.no_input && std::io::stdin().is_terminal()
+}
+
+/// Resolves the value of every template variable. Values come from, in increasing order of priority,
+/// the built-in variables, the manifest defaults, the terminal prompts, the answers file and the `--set` arguments.
+/// The derived variables are computed last, from the validated values.
+fn resolve_variables(args: &LoadTemplateArgs, manifest: &TemplateManifest, builtins: &Variables) -> Result<Variables, AppError> {
+    let mut provided = match &args.answers {
+        None => Variables::new(),
+        Some(answers) => load_answers(Path::new(answers))?,
+    };
+    for (key, value) in &args.set {
+        set_variable(&mut provided, key, toml::Value::from(value.as_str()));
+    }
+
+    let mut variables = builtins.clone();
+    merge_variables(&mut variables, manifest.default_variables());
+    merge_variables(&mut variables, provided.clone());
+
+    if is_interactive(args) {
+        let answers = prompt_variables(&manifest.variables, &provided, &variables, std::io::stdin().lock(), std::io::stderr())?;
+        merge_variables(&mut variables, answers);
+    }
+
+    let active = active_variables(&manifest.variables, &mut variables)?;
+    validate_variables(&active, &mut variables)?;
+    compute_derived(&manifest.derived, &mut variables)?;
+
+    Ok(variables)
+}
+
 /// This function initializes the template directory, as well as the config file.
 /// This function is called when the user runs the command `init`.
 /// # Arguments
@@ -80,12 +223,27 @@ pub fn delete_init_function() -> Result<(), AppError> {
     Ok(())
 }
 
+/// Copies the source directory into the staging directory and runs the pre-save hooks there,
+/// so the original directory is never changed by them.
+fn stage_template(source: &Path, staging: &Path, filter: &SaveFilter, hooks: &[String], environment: &[(String, String)]) -> Result<(), AppError> {
+    if staging.exists() {
+        std::fs::remove_dir_all(staging)?;
+    }
+    std::fs::create_dir_all(staging)?;
+
+    copy_to_dest(source, staging, filter)?;
+    run_hooks(hooks, staging, environment)
+}
+
 /// This function saves a template to the template directory.
+/// When pre-save hooks are set in the config or passed with `--hook`, the directory is first copied into a staging
+/// directory where the hooks run, and the staged copy is saved.
 ///
 /// # Arguments
 ///
 /// * `args`: A SaveTemplateArgs object
-/// (name of the template, path to the template that needs saving, overwrite the template if it already exists)
+///   (name of the template, path to the template that needs saving, overwrite the template if it already exists,
+///   extra pre-save hooks)
 ///
 /// returns: Result<(), AppError>
 ///
@@ -97,7 +255,11 @@ pub fn delete_init_function() -> Result<(), AppError> {
 /// let args = SaveTemplateArgs {
 ///     name: "test".to_string(),
 ///     path: "/tmp/app".to_string(),
-///     overwrite: true
+///     overwrite: true,
+///     hook: vec!["cargo clean".to_string()],
+///     exclude: vec!["target/".to_string()],
+///     git_tracked: false,
+///     no_gitignore: false
 /// };
 ///
 /// save_template_function(&args)?;
@@ -112,20 +274,52 @@ pub fn save_template_function(args: &SaveTemplateArgs) -> Result<(), AppError> {
 
     check_config(&config)?;
 
+    load_manifest(path)?;
+
     if config.template_absolute_path.join(name).exists() && !overwrite {
         return Err(AppError::TemplateAlreadyExists);
     }
 
+    let filter = SaveFilter {
+        exclude: args.exclude.clone(),
+        git: match (args.git_tracked, args.no_gitignore) {
+            (true, _) => GitFilter::Tracked,
+            (false, true) => GitFilter::Everything,
+            (false, false) => GitFilter::NotIgnored,
+        },
+    };
+
+    let hooks: Vec<String> = config.pre_save_hooks.iter().chain(&args.hook).cloned().collect();
+    let staging = std::env::temp_dir().join(format!("{}-staging-{}", app_name!(), std::process::id()));
+    let source = if hooks.is_empty() {
+        path
+    } else {
+        let mut variables = Variables::new();
+        variables.insert("template_name".to_string(), toml::Value::from(name.as_str()));
+        variables.insert("source_dir".to_string(), toml::Value::from(path.canonicalize()?.to_string_lossy().to_string()));
+
+        if let Err(error) = stage_template(path, &staging, &filter, &hooks, &hook_environment(&variables)) {
+            std::fs::remove_dir_all(&staging).ok();
+            return Err(error);
+        }
+        staging.as_path()
+    };
+
     if overwrite {
         std::fs::remove_dir_all(config.template_absolute_path.join(name))?;
     }
 
     let destination = config.template_absolute_path.join(name);
-    let source = path;
 
     std::fs::create_dir_all(&destination)?;
 
-    copy_to_dest(source, &destination)?;
+    // The staging directory was filtered when it was created, everything left in it after the hooks is saved
+    let filter = if hooks.is_empty() { filter } else { SaveFilter { exclude: Vec::new(), git: GitFilter::Everything } };
+    copy_to_dest(source, &destination, &filter)?;
+
+    if !hooks.is_empty() {
+        std::fs::remove_dir_all(&staging)?;
+    }
 
     config.templates.push(name.to_string());
     config.templates.sort();
@@ -145,7 +339,129 @@ pub fn load_template_function(args: &LoadTemplateArgs) -> Result<(), AppError> {
 
     let source = config.template_absolute_path.join(name);
 
-    copy_to_dest(&source, &path)?;
+    let manifest = load_manifest(&source)?.unwrap_or_default();
+    // Pre-load scripts run in every mode, so a template with them is checked even when the hooks are skipped
+    if (!args.no_hooks && !args.dry_run) || !manifest.scripts.pre_load.is_empty() {
+        let code = TemplateCode::load(&source, &manifest.hooks, &manifest.scripts)?;
+        check_trusted(&load_trust_store(&trust_store_path()?)?, name, &code)?;
+    }
+
+    let builtins = builtin_variables(name, &path);
+    let mut variables = resolve_variables(args, &manifest, &builtins)?;
+
+    // Pre-load scripts run before the conflicts are checked, so they cannot change files
+    let pre_load_scripts = ScriptRunner::new(&source, &path, false)?;
+    let mut computed = HashSet::new();
+    for script in &manifest.scripts.pre_load {
+        let values = pre_load_scripts.compute(script, &variables)?;
+        computed.extend(values.keys().cloned());
+        merge_variables(&mut variables, values);
+    }
+
+    let always_excluded: Vec<String> = manifest.partials.iter().chain(manifest.scripts.paths()).cloned().collect();
+    let rules = PathRules {
+        excluded: excluded_paths(&manifest.conditional_paths, &always_excluded, &variables)?,
+        verbatim: build_glob_set(&manifest.verbatim)?,
+        repeated: repetitions(&manifest.repeated_paths, &variables)?,
+    };
+    let engine = TemplateEngine::new(&source, &variables, &manifest.syntax)?;
+
+    let mut plan = render_to_plan(&source, &engine, &rules)?;
+
+    let answers: Variables = variables
+        .iter()
+        .filter(|(key, value)| builtins.get(*key) != Some(*value) && !manifest.derived.contains_key(*key) && !computed.contains(*key))
+        .map(|(key, value)| (key.clone(), value.clone()))
+        .collect();
+    if !answers.is_empty() {
+        let answers = answers_to_string(&merge_answers(&path.join(answers_file_name!()), answers)?)?.into_bytes();
+        // A file name of the template can still render to the answers file, it is replaced by the answers
+        match plan.files.iter_mut().find(|file| file.path == Path::new(answers_file_name!())) {
+            Some(file) => {
+                file.source = None;
+                file.content = answers;
+                file.permissions = None;
+            }
+            None => plan.add_file(PathBuf::from(answers_file_name!()), None, answers, None),
+        }
+    }
+
+    plan.resolve_actions(&path)?;
+    // The answers file holds the answers of every template loaded into the project, it is not a user file
+    if let Some(file) = plan.files.iter_mut().find(|file| file.path == Path::new(answers_file_name!())) {
+        file.conflict = false;
+    }
+    merge_files(&mut plan, &path, &build_glob_set(&manifest.merge)?)?;
+
+    // Without a terminal nobody can answer, so `prompt` fails on conflicts like `fail`
+    let on_conflict = match args.on_conflict {
+        ConflictPolicy::Prompt if !is_interactive(args) => ConflictPolicy::Fail,
+        policy => policy,
+    };
+
+    // A dry run shows the conflicts instead of failing or asking about them
+    if !args.dry_run || !matches!(on_conflict, ConflictPolicy::Fail | ConflictPolicy::Prompt) {
+        resolve_conflicts(&mut plan, on_conflict, &path, std::io::stdin().lock(), std::io::stderr())?;
+    }
+    apply_injections(&mut plan, &manifest.injections, &engine, &variables, &path)?;
+
+    if args.dry_run {
+        if args.json {
+            println!("{}", plan.to_json()?);
+        } else {
+            plan.print(std::io::stdout())?;
+        }
+        return Ok(());
+    }
+
+    watch_interrupts()?;
+    plan.write(&path)?;
+    print_conflicts(&plan, std::io::stdout())?;
+    print_merges(&plan, std::io::stdout())?;
+    print_injections(&plan, std::io::stdout())?;
+
+    // Post-load scripts can write files that run later, so they are skipped with the hooks
+    if !args.no_hooks {
+        let post_load_scripts = ScriptRunner::new(&source, &path, true)?;
+        for script in &manifest.scripts.post_load {
+            post_load_scripts.run(script, &variables)?;
+        }
+
+        run_hooks(&manifest.hooks.post_load, &path, &hook_environment(&variables))?;
+    }
+
+    Ok(())
+}
+
+/// Records the current hooks and scripts of a template as trusted, so `load-template` runs them.
+/// They are printed so the user sees what was approved.
+///
+/// # Arguments
+///
+/// * `args`: &TrustArgs - The name of the template
+///
+/// returns: Result<(), AppError>
+pub fn trust_template_function(args: &TrustArgs) -> Result<(), AppError> {
+    let name = &args.name;
+
+    let config = confy::load::<InitialConfig>(app_name!(), config_name!())?;
+    check_config(&config)?;
+
+    config.templates.iter().find(|&x| x == name).ok_or(AppError::TemplateDoesNotExist)?;
+
+    let source = config.template_absolute_path.join(name);
+    let manifest = load_manifest(&source)?.unwrap_or_default();
+    let code = TemplateCode::load(&source, &manifest.hooks, &manifest.scripts)?;
+
+    let path = trust_store_path()?;
+    let mut store = load_trust_store(&path)?;
+    store.trust(name, code_hash(&code)?);
+    store_trust_store(&path, &store)?;
+
+    println!("Trusted the hooks and scripts of `{}`:", name);
+    for description in code.descriptions() {
+        println!("\t- {}", description);
+    }
 
     Ok(())
 }
@@ -157,9 +473,35 @@ pub fn show_config() -> Result<(), AppError> {
     println!("Config file path: {}", confy::get_configuration_file_path(app_name!(), config_name!())?.to_str().unwrap());
     println!("Version: {}", config.version);
     println!("Template directory: {}", config.template_absolute_path.to_str().unwrap());
+    if !config.pre_save_hooks.is_empty() {
+        println!("Pre-save hooks: {}", config.pre_save_hooks.join(", "));
+    }
     println!("Templates: ");
     for template in config.templates {
-        println!("\t- {}", template);
+        let manifest = match load_manifest(&config.template_absolute_path.join(&template))? {
+            None => {
+                println!("\t- {}", template);
+                continue;
+            }
+            Some(manifest) => manifest,
+        };
+
+        match &manifest.description {
+            None => println!("\t- {}", template),
+            Some(description) => println!("\t- {}: {}", template, description),
+        }
+        if let Some(author) = &manifest.author {
+            println!("\t  Author: {}", author);
+        }
+        if !manifest.tags.is_empty() {
+            println!("\t  Tags: {}", manifest.tags.join(", "));
+        }
+        for variable in &manifest.variables {
+            let default = variable.default.as_ref().map(|default| format!(" [default: {}]", value_to_string(default))).unwrap_or_default();
+            let when = variable.when.as_ref().map(|when| format!(" [when: {}]", when)).unwrap_or_default();
+            let help = variable.help.as_ref().map(|help| format!(" - {}", help)).unwrap_or_default();
+            println!("\t  Variable {} ({}){}{}{}", variable.name, variable.variable_type, default, when, help);
+        }
     }
 
     Ok(())
@@ -171,8 +513,13 @@ mod tests {
     use crate::constants::{APP_NAME, CONFIG_NAME, APP_VERSION, TEMPLATE_FOLDER_NAME};
     use crate::{AppError, InitPushArgs};
     use crate::{app_name, app_version, config_name, template_path, template_folder_name};
-    use crate::functionality::{delete_init_function, init_function};
+    use crate::functionality::{delete_init_function, init_function, render_to_plan, stage_template, PathRules};
+    use crate::ignore_module::SaveFilter;
+    use crate::conditions_module::build_glob_set;
+    use crate::render_module::Variables;
+    use crate::repeat_module::{repetitions, RepeatedPaths};
     use crate::template_config_module::InitialConfig;
+    use crate::template_engine_module::{TemplateEngine, TemplateSyntax};
 
     #[test]
     fn test_init_function() -> Result<(), AppError> {
@@ -248,7 +595,11 @@ mod tests {
         let args = crate::SaveTemplateArgs {
             path: "/tmp/template/".to_string(),
             name: "test".to_string(),
-            overwrite: false
+            overwrite: false,
+            hook: Vec::new(),
+            exclude: Vec::new(),
+            git_tracked: false,
+            no_gitignore: false
         };
 
         std::fs::create_dir_all("/tmp/template/")?;
@@ -268,4 +619,105 @@ mod tests {
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_render_to_plan() -> Result<(), AppError> {
+        let source = PathBuf::from("/tmp/templater_render_source/");
+        let dest = PathBuf::from("/tmp/templater_render_dest/");
+        let binary = vec![0u8, 159, 146, 150, b'{', b'{', b'}', b'}'];
+
+        std::fs::create_dir_all(source.join("src/{{crate_name}}"))?;
+        std::fs::create_dir_all(source.join("docker"))?;
+        std::fs::create_dir_all(source.join(".github/workflows"))?;
+        std::fs::create_dir_all(&dest)?;
+        std::fs::write(source.join("Cargo.toml"), "name = \"{{ project_name }}\"")?;
+        std::fs::write(source.join("src/main.rs"), "// {{project_name}}{% if crate_name %} {{ crate_name }}{% endif %}")?;
+        std::fs::write(source.join("src/{{crate_name}}/mod.rs"), "")?;
+        std::fs::write(source.join("{{project_name}}.service"), "")?;
+        std::fs::write(source.join("logo.png"), &binary)?;
+        std::fs::write(source.join(".github/workflows/ci.yml"), "token: ${{ secrets.TOKEN }}")?;
+        std::fs::write(source.join("template.toml"), "description = \"{{project_name}}\"")?;
+        std::fs::write(source.join(".templater-answers.toml"), "project_name = \"old-app\"")?;
+        std::fs::create_dir_all(source.join("src/handlers"))?;
+        std::fs::create_dir_all(source.join("services/{{ service }}"))?;
+        std::fs::write(source.join("src/handlers/{{ item }}.rs"), "pub fn {{ item }}() {}")?;
+        std::fs::write(source.join("services/{{ service }}/Dockerfile"), "FROM {{ service }}")?;
+
+        let mut variables = Variables::new();
+        variables.insert("project_name".to_string(), toml::Value::from("my-app"));
+        variables.insert("crate_name".to_string(), toml::Value::from("my_app"));
+        variables.insert("handlers".to_string(), toml::Value::from(vec!["users", "orders"]));
+        variables.insert("services".to_string(), toml::Value::from(vec!["api"]));
+
+        let repeated_paths = vec![
+            RepeatedPaths {
+                paths: vec!["src/handlers/*.rs".to_string()],
+                variable: "handlers".to_string(),
+                item: "item".to_string(),
+            },
+            RepeatedPaths {
+                paths: vec!["services/*".to_string()],
+                variable: "services".to_string(),
+                item: "service".to_string(),
+            },
+        ];
+
+        let rules = PathRules {
+            excluded: build_glob_set(["docker"])?,
+            verbatim: build_glob_set([".github/workflows/*.yml"])?,
+            repeated: repetitions(&repeated_paths, &variables)?,
+        };
+        let engine = TemplateEngine::new(&source, &variables, &TemplateSyntax::default())?;
+
+        render_to_plan(&source, &engine, &rules)?.write(&dest)?;
+
+        assert_eq!(std::fs::read_to_string(dest.join("Cargo.toml"))?, "name = \"my-app\"");
+        assert_eq!(std::fs::read_to_string(dest.join("src/main.rs"))?, "// my-app my_app");
+        assert_eq!(std::fs::read(dest.join("logo.png"))?, binary);
+        assert!(dest.join("src/my_app/mod.rs").exists());
+        assert!(dest.join("my-app.service").exists());
+        assert!(!dest.join("template.toml").exists());
+        assert!(!dest.join(".templater-answers.toml").exists());
+        assert!(!dest.join("docker").exists());
+        assert_eq!(std::fs::read_to_string(dest.join(".github/workflows/ci.yml"))?, "token: ${{ secrets.TOKEN }}");
+        assert_eq!(std::fs::read_to_string(dest.join("src/handlers/users.rs"))?, "pub fn users() {}");
+        assert_eq!(std::fs::read_to_string(dest.join("src/handlers/orders.rs"))?, "pub fn orders() {}");
+        assert_eq!(std::fs::read_to_string(dest.join("services/api/Dockerfile"))?, "FROM api");
+
+        std::fs::remove_dir_all(&source)?;
+        std::fs::remove_dir_all(&dest)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_stage_template() -> Result<(), AppError> {
+        let source = PathBuf::from("/tmp/templater_stage_source/");
+        let staging = PathBuf::from("/tmp/templater_stage_staging/");
+
+        std::fs::create_dir_all(source.join("target/debug"))?;
+        std::fs::write(source.join("main.rs"), "fn main() {}")?;
+        std::fs::write(source.join("target/debug/app"), "")?;
+
+        let hooks = vec!["rm -r target".to_string(), "echo \"$TEMPLATER_TEMPLATE_NAME\" > name.txt".to_string()];
+        let environment = vec![("TEMPLATER_TEMPLATE_NAME".to_string(), "rust-cli".to_string())];
+
+        stage_template(&source, &staging, &SaveFilter::default(), &hooks, &environment)?;
+
+        assert!(staging.join("main.rs").exists());
+        assert!(!staging.join("target").exists());
+        assert_eq!(std::fs::read_to_string(staging.join("name.txt"))?, "rust-cli\n");
+        assert!(source.join("target/debug/app").exists());
+        assert!(!source.join("name.txt").exists());
+
+        assert!(matches!(
+            stage_template(&source, &staging, &SaveFilter::default(), &["false".to_string()], &environment),
+            Err(AppError::HookFailed { .. })
+        ));
+
+        std::fs::remove_dir_all(&source)?;
+        std::fs::remove_dir_all(&staging)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+use std::path::Path;
+use std::process::Command;
+use serde::{Deserialize, Serialize};
+use crate::render_module::{value_to_string, Variables};
+use crate::AppError;
+
+/// The prefix of the environment variables that hold the template variables while a hook runs.
+const HOOK_VARIABLE_PREFIX: &str = "TEMPLATER_";
+
+/// The commands a template runs around loading, set in the `[hooks]` table of the manifest.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateHooks {
+    /// Commands run in the target directory after the template is loaded
+    #[serde(default)]
+    pub post_load: Vec<String>,
+}
+
+/// Returns the template variables as environment variables for the hooks.
+/// A variable `project_name` becomes `TEMPLATER_PROJECT_NAME`, tables are flattened (`TEMPLATER_USER_EMAIL`)
+/// and lists are joined with commas.
+///
+/// # Arguments
+///
+/// * `variables`: &Variables - The resolved variables
+///
+/// returns: Vec<(String, String)>
+pub fn hook_environment(variables: &Variables) -> Vec<(String, String)> {
+    let mut environment = Vec::new();
+    for (name, value) in variables {
+        add_environment_variable(&mut environment, &format!("{}{}", HOOK_VARIABLE_PREFIX, name), value);
+    }
+    environment
+}
+
+fn add_environment_variable(environment: &mut Vec<(String, String)>, name: &str, value: &toml::Value) {
+    match value {
+        toml::Value::Table(table) => {
+            for (key, value) in table {
+                add_environment_variable(environment, &format!("{}_{}", name, key), value);
+            }
+        }
+        toml::Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(value_to_string).collect();
+            environment.push((name.to_uppercase(), items.join(",")));
+        }
+        value => environment.push((name.to_uppercase(), value_to_string(value))),
+    }
+}
+
+/// Runs the given commands one after the other with the system shell, in `directory` and with the extra environment.
+/// Their output is shown as it is produced and the first failing command stops the others.
+///
+/// # Arguments
+///
+/// * `commands`: &[String] - The commands to run, e.g. `git init` or `chmod +x scripts/*`
+/// * `directory`: &Path - The working directory of the commands
+/// * `environment`: &[(String, String)] - The environment variables added for the commands
+///
+/// returns: Result<(), AppError>
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use std::path::Path;
+/// use cli::hooks_module::{hook_environment, run_hooks};
+///
+/// run_hooks(&manifest.hooks.post_load, Path::new("/home/user/my-app"), &hook_environment(&variables))?;
+/// ```
+pub fn run_hooks(commands: &[String], directory: &Path, environment: &[(String, String)]) -> Result<(), AppError> {
+    for command in commands {
+        println!("Running hook `{}`", command);
+
+        let status = shell_command(command)
+            .current_dir(directory)
+            .envs(environment.iter().map(|(name, value)| (name, value)))
+            .status()?;
+
+        if !status.success() {
+            return Err(AppError::HookFailed {
+                command: command.clone(),
+                status: status.to_string(),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(windows)]
+fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("cmd");
+    shell.args(["/C", command]);
+    shell
+}
+
+#[cfg(not(windows))]
+fn shell_command(command: &str) -> Command {
+    let mut shell = Command::new("sh");
+    shell.args(["-c", command]);
+    shell
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::AppError;
+    use crate::hooks_module::{hook_environment, run_hooks};
+    use crate::render_module::Variables;
+    use crate::variables_module::set_variable;
+
+    #[test]
+    fn test_hook_environment() {
+        let mut variables = Variables::new();
+        variables.insert("project_name".to_string(), toml::Value::from("my-app"));
+        variables.insert("handlers".to_string(), toml::Value::from(vec!["users", "orders"]));
+        set_variable(&mut variables, "user.email", toml::Value::from("me@example.com"));
+
+        let environment = hook_environment(&variables);
+
+        assert!(environment.contains(&("TEMPLATER_PROJECT_NAME".to_string(), "my-app".to_string())));
+        assert!(environment.contains(&("TEMPLATER_HANDLERS".to_string(), "users,orders".to_string())));
+        assert!(environment.contains(&("TEMPLATER_USER_EMAIL".to_string(), "me@example.com".to_string())));
+    }
+
+    #[test]
+    fn test_run_hooks() -> Result<(), AppError> {
+        let directory = Path::new("/tmp/templater_hooks_target/");
+        std::fs::create_dir_all(directory)?;
+        let environment = vec![("TEMPLATER_PROJECT_NAME".to_string(), "my-app".to_string())];
+
+        run_hooks(&["echo \"$TEMPLATER_PROJECT_NAME\" > name.txt".to_string()], directory, &environment)?;
+
+        assert_eq!(std::fs::read_to_string(directory.join("name.txt"))?, "my-app\n");
+
+        let commands = vec!["exit 3".to_string(), "touch never.txt".to_string()];
+        match run_hooks(&commands, directory, &environment) {
+            Err(AppError::HookFailed { command, status }) => {
+                assert_eq!(command, "exit 3");
+                assert!(status.contains('3'));
+            }
+            other => panic!("Expected a failed hook, got {:?}", other),
+        }
+        assert!(!directory.join("never.txt").exists());
+
+        std::fs::remove_dir_all(directory)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,195 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use ignore::overrides::OverrideBuilder;
+use ignore::WalkBuilder;
+use crate::template_ignore_name;
+use crate::constants::TEMPLATE_IGNORE_NAME;
+use crate::AppError;
+
+/// Which files of a git repository are saved.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum GitFilter {
+    /// Every file, including the `.git` directory
+    Everything,
+    /// Every file except the `.git` directory and the files ignored by `.gitignore`, `.git/info/exclude`
+    /// and the global git excludes file, like ripgrep does
+    #[default]
+    NotIgnored,
+    /// Only the files tracked by git
+    Tracked,
+}
+
+/// The rules that decide which paths of a directory are saved as a template.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SaveFilter {
+    /// Globs of paths to leave out, in gitignore syntax (e.g. `target/` or `*.swp`)
+    pub exclude: Vec<String>,
+    pub git: GitFilter,
+}
+
+/// Returns the paths under a directory that are saved as a template, relative to it and parents before their content.
+/// Paths matched by a `.templateignore` file (gitignore syntax, in the directory or any subdirectory) or by one of
+/// the `exclude` globs are left out, and their directories are not walked. The `.templateignore` files are left out too.
+/// Inside a git repository, the `git` filter also leaves out the files git ignores or does not track.
+///
+/// # Arguments
+///
+/// * `source`: &Path - The directory that is saved
+/// * `filter`: &SaveFilter - The rules for the saved paths
+///
+/// returns: Result<Vec<PathBuf>, AppError>
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use std::path::Path;
+/// use cli::ignore_module::{saved_paths, GitFilter, SaveFilter};
+///
+/// let filter = SaveFilter { exclude: vec!["node_modules/".to_string()], git: GitFilter::NotIgnored };
+///
+/// for relative_path in saved_paths(Path::new("/home/user/my-app"), &filter)? {
+///     println!("{}", relative_path.display());
+/// }
+/// ```
+pub fn saved_paths(source: &Path, filter: &SaveFilter) -> Result<Vec<PathBuf>, AppError> {
+    let mut overrides = OverrideBuilder::new(source);
+    for pattern in &filter.exclude {
+        overrides.add(&format!("!{}", pattern))?;
+    }
+
+    let honor_git = filter.git != GitFilter::Everything;
+    let walker = WalkBuilder::new(source)
+        .standard_filters(false)
+        .git_ignore(honor_git)
+        .git_exclude(honor_git)
+        .git_global(honor_git)
+        .add_custom_ignore_filename(template_ignore_name!())
+        .overrides(overrides.build()?)
+        .filter_entry(move |entry| !honor_git || entry.file_name() != ".git")
+        .sort_by_file_name(|first, second| first.cmp(second))
+        .build();
+
+    let tracked = match filter.git {
+        GitFilter::Tracked => Some(tracked_paths(source)?),
+        _ => None,
+    };
+
+    let mut paths = Vec::new();
+    for entry in walker {
+        let entry = entry?;
+
+        if entry.depth() == 0 || entry.file_name() == template_ignore_name!() {
+            continue;
+        }
+
+        let relative_path = match entry.path().strip_prefix(source) {
+            Ok(relative_path) => relative_path.to_path_buf(),
+            Err(_) => continue,
+        };
+
+        if tracked.as_ref().is_none_or(|tracked| tracked.contains(&relative_path)) {
+            paths.push(relative_path);
+        }
+    }
+
+    Ok(paths)
+}
+
+/// Returns the files git tracks under a directory, relative to it, together with the directories that hold them.
+fn tracked_paths(source: &Path) -> Result<HashSet<PathBuf>, AppError> {
+    let output = Command::new("git")
+        .args(["ls-files", "-z", "--cached"])
+        .current_dir(source)
+        .output()
+        .map_err(|error| AppError::GitTrackedFiles(error.to_string()))?;
+
+    if !output.status.success() {
+        return Err(AppError::GitTrackedFiles(String::from_utf8_lossy(&output.stderr).trim().to_string()));
+    }
+
+    let mut tracked = HashSet::new();
+    for file in String::from_utf8_lossy(&output.stdout).split('\0').filter(|file| !file.is_empty()) {
+        tracked.extend(Path::new(file).ancestors().filter(|path| !path.as_os_str().is_empty()).map(Path::to_path_buf));
+    }
+
+    Ok(tracked)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+    use std::process::Command;
+    use crate::AppError;
+    use crate::ignore_module::{saved_paths, GitFilter, SaveFilter};
+
+    fn paths(list: &[&str]) -> Vec<PathBuf> {
+        list.iter().map(PathBuf::from).collect()
+    }
+
+    #[test]
+    fn test_saved_paths() -> Result<(), AppError> {
+        let source = Path::new("/tmp/templater_ignore_source/");
+        std::fs::create_dir_all(source.join("target/debug"))?;
+        std::fs::create_dir_all(source.join("src/node_modules"))?;
+        std::fs::create_dir_all(source.join("docs"))?;
+        std::fs::write(source.join(".templateignore"), "target/\n*.swp\n")?;
+        std::fs::write(source.join("target/debug/app"), "")?;
+        std::fs::write(source.join("src/main.rs"), "")?;
+        std::fs::write(source.join("src/.main.rs.swp"), "")?;
+        std::fs::write(source.join("src/node_modules/index.js"), "")?;
+        std::fs::write(source.join("docs/.templateignore"), "draft.md\n")?;
+        std::fs::write(source.join("docs/draft.md"), "")?;
+        std::fs::write(source.join("docs/guide.md"), "")?;
+
+        let filter = SaveFilter {
+            exclude: vec!["node_modules/".to_string()],
+            git: GitFilter::NotIgnored,
+        };
+
+        assert_eq!(saved_paths(source, &filter)?, paths(&["docs", "docs/guide.md", "src", "src/main.rs"]));
+
+        std::fs::remove_dir_all(source)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_saved_paths_git() -> Result<(), AppError> {
+        let source = Path::new("/tmp/templater_ignore_git_source/");
+        std::fs::create_dir_all(source.join("src"))?;
+        std::fs::create_dir_all(source.join("target"))?;
+        std::fs::write(source.join(".gitignore"), "target/\n")?;
+        std::fs::write(source.join("src/main.rs"), "")?;
+        std::fs::write(source.join("target/app"), "")?;
+        std::fs::write(source.join(".env"), "SECRET=1")?;
+        std::fs::write(source.join("notes.txt"), "")?;
+
+        let git = |args: &[&str]| Command::new("git").args(args).current_dir(source).output();
+        git(&["init", "-q"])?;
+        std::fs::write(source.join(".git/info/exclude"), ".env\n")?;
+        git(&["add", ".gitignore", "src/main.rs"])?;
+
+        let filter = |git| SaveFilter { exclude: Vec::new(), git };
+
+        assert_eq!(
+            saved_paths(source, &filter(GitFilter::NotIgnored))?,
+            paths(&[".gitignore", "notes.txt", "src", "src/main.rs"])
+        );
+        assert_eq!(saved_paths(source, &filter(GitFilter::Tracked))?, paths(&[".gitignore", "src", "src/main.rs"]));
+
+        let everything = saved_paths(source, &filter(GitFilter::Everything))?;
+        assert!(everything.contains(&PathBuf::from(".git")));
+        assert!(everything.contains(&PathBuf::from(".env")));
+        assert!(everything.contains(&PathBuf::from("target/app")));
+
+        std::fs::remove_dir_all(source)?;
+
+        assert!(matches!(
+            saved_paths(Path::new("/tmp"), &filter(GitFilter::Tracked)),
+            Err(AppError::GitTrackedFiles(_))
+        ));
+
+        Ok(())
+    }
+}
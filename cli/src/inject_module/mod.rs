@@ -0,0 +1,349 @@
+use std::io::Write;
+use std::path::{Component, Path, PathBuf};
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use crate::plan_module::{LoadPlan, PlanAction};
+use crate::render_module::Variables;
+use crate::template_engine_module::{evaluate_expression, TemplateEngine};
+use crate::AppError;
+
+/// Where the injected lines go, relative to the line that holds the marker.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InjectPosition {
+    #[default]
+    After,
+    Before,
+}
+
+/// Lines inserted into a file of the target directory, set with `[[injections]]` in the manifest.
+/// The file and the content are rendered with the template variables. The lines are inserted next to the first line
+/// that contains `marker`, or that matches `regex`, and only once: lines that are already there are not added again.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Injection {
+    /// The path of the changed file, relative to the target directory
+    pub file: String,
+    /// The lines to insert
+    pub content: String,
+    /// A text the line to insert next to contains, usually a comment such as `// templater: modules`
+    pub marker: Option<String>,
+    /// A regex the line to insert next to matches, instead of a marker
+    pub regex: Option<String>,
+    #[serde(default)]
+    pub position: InjectPosition,
+    /// An expression of the template language, the lines are only inserted when it holds
+    pub when: Option<String>,
+}
+
+/// The line an injection is inserted next to.
+enum Anchor {
+    Marker(String),
+    Regex(Regex),
+}
+
+impl Anchor {
+    fn matches(&self, line: &str) -> bool {
+        match self {
+            Anchor::Marker(marker) => line.contains(marker.as_str()),
+            Anchor::Regex(regex) => regex.is_match(line),
+        }
+    }
+}
+
+impl Injection {
+    fn anchor(&self, file: &str) -> Result<Anchor, AppError> {
+        let error = |message: String| AppError::Injection {
+            file: file.to_string(),
+            message,
+        };
+
+        match (&self.marker, &self.regex) {
+            (Some(marker), None) => Ok(Anchor::Marker(marker.clone())),
+            (None, Some(pattern)) => Regex::new(pattern)
+                .map(Anchor::Regex)
+                .map_err(|regex_error| error(format!("the regex `{}` is invalid: {}", pattern, regex_error))),
+            _ => Err(error("exactly one of `marker` and `regex` must be set".to_string())),
+        }
+    }
+
+    /// Returns the text of the marker or the regex, for messages.
+    fn anchor_text(&self) -> &str {
+        self.marker.as_deref().or(self.regex.as_deref()).unwrap_or_default()
+    }
+}
+
+/// Inserts lines into the content of a file next to the first line that holds the marker of the injection.
+///
+/// # Arguments
+///
+/// * `injection`: &Injection - The injection, with its marker or regex
+/// * `file`: &str - The path of the file, used in error messages
+/// * `content`: &str - The content of the file
+/// * `lines`: &str - The rendered lines to insert
+///
+/// returns: Result<Option<String>, AppError> - The new content, or None when the lines are already in the file
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use cli::inject_module::{inject_lines, Injection};
+///
+/// let injection = Injection { marker: Some("// modules".to_string()), ..Default::default() };
+///
+/// let content = inject_lines(&injection, "src/lib.rs", "// modules\nmod app;\n", "mod users;")?;
+///
+/// assert_eq!(content.as_deref(), Some("// modules\nmod users;\nmod app;\n"));
+/// ```
+pub fn inject_lines(injection: &Injection, file: &str, content: &str, lines: &str) -> Result<Option<String>, AppError> {
+    let anchor = injection.anchor(file)?;
+
+    let inserted: Vec<&str> = lines.lines().collect();
+    let existing: Vec<&str> = content.lines().collect();
+    if inserted.is_empty()
+        || existing
+            .windows(inserted.len())
+            .any(|window| window.iter().zip(&inserted).all(|(line, inserted)| line.trim() == inserted.trim()))
+    {
+        return Ok(None);
+    }
+
+    let index = existing.iter().position(|line| anchor.matches(line)).ok_or_else(|| AppError::MarkerNotFound {
+        file: file.to_string(),
+        marker: injection.anchor_text().to_string(),
+    })?;
+
+    let newline = if content.contains("\r\n") { "\r\n" } else { "\n" };
+    let block: String = inserted.iter().map(|line| format!("{}{}", line, newline)).collect();
+
+    let mut result = String::new();
+    for (number, line) in content.split_inclusive('\n').enumerate() {
+        if number == index && injection.position == InjectPosition::Before {
+            result.push_str(&block);
+        }
+        result.push_str(line);
+        if number == index && injection.position == InjectPosition::After {
+            if !line.ends_with('\n') {
+                result.push_str(newline);
+            }
+            result.push_str(&block);
+        }
+    }
+
+    Ok(Some(result))
+}
+
+/// Runs the injections of the manifest against the plan, once the conflict policy is applied: a file the plan writes
+/// gets the lines in its planned content, a file kept by the policy or not in the plan is read from the target directory
+/// and written with the `inject` action.
+/// Nothing is written, so a missing marker stops the load before any file changes.
+///
+/// # Arguments
+///
+/// * `plan`: &mut LoadPlan - The plan, with its conflicts resolved
+/// * `injections`: &[Injection] - The injections declared by the template manifest
+/// * `engine`: &TemplateEngine - The engine that renders the files and the content of the injections
+/// * `variables`: &Variables - The values the `when` expressions read
+/// * `target`: &Path - The directory the template is loaded into
+///
+/// returns: Result<(), AppError>
+pub fn apply_injections(
+    plan: &mut LoadPlan,
+    injections: &[Injection],
+    engine: &TemplateEngine,
+    variables: &Variables,
+    target: &Path,
+) -> Result<(), AppError> {
+    for injection in injections {
+        if let Some(condition) = &injection.when {
+            if !evaluate_expression(condition, variables)?.is_true() {
+                continue;
+            }
+        }
+
+        let file = engine.render_str("template.toml", &injection.file)?;
+        let lines = engine.render_str("template.toml", &injection.content)?;
+        let path = PathBuf::from(&file);
+        let error = |message: &str| AppError::Injection {
+            file: file.clone(),
+            message: message.to_string(),
+        };
+
+        if path.components().any(|component| !matches!(component, Component::Normal(_) | Component::CurDir)) {
+            return Err(error("the file is outside the target directory"));
+        }
+
+        match plan.files.iter_mut().find(|planned| planned.path == path) {
+            Some(planned) => {
+                // A skipped file keeps its existing content, so the lines go into that content
+                let content = if planned.action == PlanAction::Skip {
+                    std::fs::read(target.join(&path))?
+                } else {
+                    planned.content.clone()
+                };
+                let content = String::from_utf8(content).map_err(|_| error("the file is not text"))?;
+                if let Some(content) = inject_lines(injection, &file, &content, &lines)? {
+                    planned.content = content.into_bytes();
+                    if matches!(planned.action, PlanAction::Unchanged | PlanAction::Skip) {
+                        planned.action = PlanAction::Inject;
+                    }
+                }
+            }
+            None => {
+                if !target.join(&path).is_file() {
+                    return Err(error("the file does not exist"));
+                }
+                let content = std::fs::read_to_string(target.join(&path)).map_err(|_| error("the file is not text"))?;
+                if let Some(content) = inject_lines(injection, &file, &content, &lines)? {
+                    plan.add_file(path, None, content.into_bytes(), None);
+                    if let Some(planned) = plan.files.last_mut() {
+                        planned.action = PlanAction::Inject;
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints the files of a written plan that only changed by injected lines.
+///
+/// # Arguments
+///
+/// * `plan`: &LoadPlan - The written plan
+/// * `output`: W - Where the summary is written
+///
+/// returns: Result<(), AppError>
+pub fn print_injections<W: Write>(plan: &LoadPlan, mut output: W) -> Result<(), AppError> {
+    let injected: Vec<_> = plan.files.iter().filter(|file| file.action == PlanAction::Inject).collect();
+    if injected.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(output, "Injected lines into:")?;
+    for file in injected {
+        writeln!(output, "\t{}", file.path.display())?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+    use crate::AppError;
+    use crate::inject_module::{apply_injections, inject_lines, InjectPosition, Injection};
+    use crate::plan_module::{LoadPlan, PlanAction};
+    use crate::render_module::Variables;
+    use crate::template_engine_module::{TemplateEngine, TemplateSyntax};
+
+    fn marker(marker: &str) -> Injection {
+        Injection {
+            marker: Some(marker.to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_inject_lines() -> Result<(), AppError> {
+        let content = "mod app;\n// templater: modules\n\nfn main() {}\n";
+
+        let injected = inject_lines(&marker("templater: modules"), "src/lib.rs", content, "mod users;\n")?;
+        assert_eq!(injected.as_deref(), Some("mod app;\n// templater: modules\nmod users;\n\nfn main() {}\n"));
+        assert_eq!(inject_lines(&marker("templater: modules"), "src/lib.rs", &injected.unwrap(), "mod users;")?, None);
+
+        let before = Injection {
+            position: InjectPosition::Before,
+            ..marker("// end of routes")
+        };
+        assert_eq!(
+            inject_lines(&before, "src/router.rs", "let routes = [\r\n    home,\r\n    // end of routes\r\n];", "    users,")?.as_deref(),
+            Some("let routes = [\r\n    home,\r\n    users,\r\n    // end of routes\r\n];")
+        );
+
+        let regex = Injection {
+            regex: Some(r"^use \w+;$".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(inject_lines(&regex, "main.rs", "//! App\nuse std;", "use serde;")?.as_deref(), Some("//! App\nuse std;\nuse serde;\n"));
+
+        match inject_lines(&marker("// missing"), "src/lib.rs", content, "mod users;") {
+            Err(AppError::MarkerNotFound { file, marker }) => {
+                assert_eq!(file, "src/lib.rs");
+                assert_eq!(marker, "// missing");
+            }
+            other => panic!("Expected a missing marker, got {:?}", other),
+        }
+        assert!(matches!(inject_lines(&Injection::default(), "src/lib.rs", content, "mod users;"), Err(AppError::Injection { .. })));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_apply_injections() -> Result<(), AppError> {
+        let target = Path::new("/tmp/templater_inject_target/");
+        std::fs::create_dir_all(target.join("src"))?;
+        std::fs::write(target.join("src/lib.rs"), "// modules\n")?;
+
+        let mut variables = Variables::new();
+        variables.insert("name".to_string(), toml::Value::from("users"));
+        variables.insert("router".to_string(), toml::Value::from(false));
+        let engine = TemplateEngine::new(target, &variables, &TemplateSyntax::default())?;
+
+        let injections = vec![
+            Injection {
+                file: "src/lib.rs".to_string(),
+                content: "pub mod {{ name }};".to_string(),
+                ..marker("// modules")
+            },
+            Injection {
+                file: "src/{{ name }}.rs".to_string(),
+                content: "pub fn {{ name }}() {}".to_string(),
+                ..marker("// handlers")
+            },
+            Injection {
+                file: "src/router.rs".to_string(),
+                content: "{{ name }},".to_string(),
+                when: Some("router".to_string()),
+                ..marker("// routes")
+            },
+        ];
+
+        let mut plan = LoadPlan::default();
+        plan.add_file(PathBuf::from("src/users.rs"), Some(PathBuf::from("src/{{ name }}.rs")), b"// handlers\n".to_vec(), None);
+        plan.resolve_actions(target)?;
+
+        apply_injections(&mut plan, &injections, &engine, &variables, target)?;
+
+        assert_eq!(plan.files[0].action, PlanAction::Create);
+        assert_eq!(plan.files[0].content, b"// handlers\npub fn users() {}\n");
+        assert_eq!(plan.files[1].path, PathBuf::from("src/lib.rs"));
+        assert_eq!(plan.files[1].action, PlanAction::Inject);
+
+        plan.write(target)?;
+        assert_eq!(std::fs::read_to_string(target.join("src/lib.rs"))?, "// modules\npub mod users;\n");
+
+        let mut plan = LoadPlan::default();
+        apply_injections(&mut plan, &injections[..1], &engine, &variables, target)?;
+        assert!(plan.files.is_empty());
+
+        std::fs::write(target.join("src/lib.rs"), "// modules\npub mod orders;\n")?;
+        plan.add_file(PathBuf::from("src/lib.rs"), Some(PathBuf::from("src/lib.rs")), b"// modules\n".to_vec(), None);
+        plan.resolve_actions(target)?;
+        plan.files[0].action = PlanAction::Skip;
+        apply_injections(&mut plan, &injections[..1], &engine, &variables, target)?;
+        assert_eq!(plan.files[0].action, PlanAction::Inject);
+        assert_eq!(plan.files[0].content, b"// modules\npub mod users;\npub mod orders;\n");
+
+        let outside = Injection {
+            file: "../lib.rs".to_string(),
+            ..injections[0].clone()
+        };
+        assert!(matches!(apply_injections(&mut plan, &[outside], &engine, &variables, target), Err(AppError::Injection { .. })));
+
+        std::fs::remove_dir_all(target)?;
+
+        Ok(())
+    }
+}
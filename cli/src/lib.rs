@@ -1,11 +1,32 @@
 mod functionality;
 pub mod template_config_module;
+pub mod template_manifest_module;
+pub mod render_module;
+pub mod template_engine_module;
+pub mod filters_module;
+pub mod prompt_module;
+pub mod answers_module;
+pub mod variables_module;
+pub mod validation_module;
+pub mod derived_module;
+pub mod conditions_module;
+pub mod repeat_module;
+pub mod hooks_module;
+pub mod trust_module;
+pub mod scripts_module;
+pub mod ignore_module;
+pub mod plan_module;
+pub mod conflict_module;
+pub mod merge_module;
+pub mod inject_module;
+pub mod transaction_module;
 pub mod app_error;
 pub mod constants;
 
 // use clap::{Args, Parser, Subcommand};
 pub use crate::app_error::AppError;
-use crate::functionality::{delete_init_function, init_function, load_template_function, save_template_function, show_config};
+use crate::conflict_module::ConflictPolicy;
+use crate::functionality::{delete_init_function, init_function, load_template_function, save_template_function, show_config, trust_template_function};
 use crate::constants::{APP_NAME, APP_AUTHOR, APP_ABOUT, APP_VERSION_STRING};
 use clap::{Args, Parser, Subcommand};
 
@@ -24,7 +45,19 @@ pub struct SaveTemplateArgs {
     pub path: String,
     /// Overwrite the template if it already exists
     #[arg(short, long, action)]
-    pub overwrite: bool
+    pub overwrite: bool,
+    /// Command run on a staging copy of the directory before it is saved, after the hooks of the config (can be repeated)
+    #[arg(long, value_name = "COMMAND")]
+    pub hook: Vec<String>,
+    /// Glob of paths left out of the saved template, in gitignore syntax (can be repeated)
+    #[arg(short, long, value_name = "GLOB")]
+    pub exclude: Vec<String>,
+    /// Only save the files tracked by git
+    #[arg(long, action, conflicts_with = "no_gitignore")]
+    pub git_tracked: bool,
+    /// Also save the files ignored by git and the `.git` directory
+    #[arg(long, action)]
+    pub no_gitignore: bool
 }
 
 #[derive(Debug, Args)]
@@ -33,6 +66,41 @@ pub struct LoadTemplateArgs {
     pub name: String,
     /// Path to the template directory that you want to save
     pub path: String,
+    /// Value for a template variable (can be repeated, overrides the answers file)
+    #[arg(short, long, value_name = "KEY=VALUE", value_parser = parse_key_value)]
+    pub set: Vec<(String, String)>,
+    /// TOML file holding the values of the template variables
+    #[arg(short, long, value_name = "FILE")]
+    pub answers: Option<String>,
+    /// Never ask for missing variables, use the template defaults instead
+    #[arg(long, action)]
+    pub no_input: bool,
+    /// Do not run the hooks and post-load scripts of the template
+    #[arg(long, action)]
+    pub no_hooks: bool,
+    /// Show what would be created or overwritten without writing anything
+    #[arg(long, action)]
+    pub dry_run: bool,
+    /// Print the plan of `--dry-run` as JSON
+    #[arg(long, action, requires = "dry_run")]
+    pub json: bool,
+    /// What to do with files that already exist with another content
+    #[arg(long, value_enum, default_value_t = ConflictPolicy::Fail)]
+    pub on_conflict: ConflictPolicy
+}
+
+#[derive(Debug, Args)]
+pub struct TrustArgs {
+    /// Name of the template whose hooks and scripts are trusted
+    pub name: String
+}
+
+/// Parses a `KEY=VALUE` pair passed on the command line.
+fn parse_key_value(argument: &str) -> Result<(String, String), String> {
+    match argument.split_once('=') {
+        Some((key, value)) if !key.trim().is_empty() => Ok((key.trim().to_string(), value.to_string())),
+        _ => Err(format!("invalid KEY=VALUE pair: `{}`", argument)),
+    }
 }
 
 #[derive(Subcommand)]
@@ -68,6 +136,12 @@ pub enum Commands {
         #[command(flatten)]
         load: LoadTemplateArgs
     },
+    /// Approve the hooks and scripts of a template so load-template runs them
+    #[command(arg_required_else_help = true)]
+    Trust {
+        #[command(flatten)]
+        trust: TrustArgs
+    },
     ShowConfig
 }
 
@@ -100,6 +174,7 @@ pub fn match_commands(cli: &Cli) -> Result<(), AppError> {
 
         Commands::SaveTemplate { save } => { save_template_function(save)? }
         Commands::LoadTemplate { load } => { load_template_function(load)? }
+        Commands::Trust { trust } => { trust_template_function(trust)? }
         Commands::ShowConfig => { show_config()? }
     }
     Ok(())
@@ -0,0 +1,346 @@
+use std::io::Write;
+use std::path::Path;
+use globset::GlobSet;
+use toml_edit::{Document, Item, TableLike, Value};
+use crate::plan_module::{LoadPlan, PlanAction};
+use crate::AppError;
+
+/// A structured file type that `load-template` can merge into an existing file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructuredFormat {
+    Toml,
+    Json,
+    Yaml,
+}
+
+impl StructuredFormat {
+    /// Returns the format of a file from its extension, if it is a known structured type.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        match path.extension()?.to_str()?.to_lowercase().as_str() {
+            "toml" => Some(StructuredFormat::Toml),
+            "json" => Some(StructuredFormat::Json),
+            "yaml" | "yml" => Some(StructuredFormat::Yaml),
+            _ => None,
+        }
+    }
+}
+
+/// Merges the rendered content of a template file into the content of an existing file.
+/// Tables are merged key by key, missing items are appended to arrays and every other value keeps
+/// its existing value. Keys whose values differ are returned as key conflicts, e.g. `package.edition`.
+/// TOML files keep their formatting and comments, JSON and YAML files are written again when something is added.
+///
+/// # Arguments
+///
+/// * `format`: StructuredFormat - The format of both contents
+/// * `existing`: &str - The content of the file in the target directory
+/// * `template`: &str - The rendered content of the template file
+///
+/// returns: Result<(String, Vec<String>), String> - The merged content and the key conflicts, or why a content could not be parsed
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use cli::merge_module::{merge_content, StructuredFormat};
+///
+/// let (merged, conflicts) = merge_content(StructuredFormat::Json, r#"{"name": "app"}"#, r#"{"name": "other", "private": true}"#)?;
+///
+/// assert_eq!(merged, "{\n  \"name\": \"app\",\n  \"private\": true\n}\n");
+/// assert_eq!(conflicts, vec!["name"]);
+/// ```
+pub fn merge_content(format: StructuredFormat, existing: &str, template: &str) -> Result<(String, Vec<String>), String> {
+    let mut conflicts = Vec::new();
+
+    let merged = match format {
+        StructuredFormat::Toml => {
+            let mut document = existing.parse::<Document>().map_err(|error| error.to_string())?;
+            let added = template.parse::<Document>().map_err(|error| format!("the template file is invalid: {}", error))?;
+            merge_toml(document.as_table_mut(), added.as_table(), "", &mut conflicts).then(|| document.to_string())
+        }
+        StructuredFormat::Json => {
+            let mut value: serde_json::Value = serde_json::from_str(existing).map_err(|error| error.to_string())?;
+            let added = serde_json::from_str(template).map_err(|error| format!("the template file is invalid: {}", error))?;
+            if merge_values(&mut value, added, "", &mut conflicts) {
+                Some(serde_json::to_string_pretty(&value).map_err(|error| error.to_string())? + "\n")
+            } else {
+                None
+            }
+        }
+        StructuredFormat::Yaml => {
+            let mut value: serde_json::Value = serde_yaml::from_str(existing).map_err(|error| error.to_string())?;
+            let added = serde_yaml::from_str(template).map_err(|error| format!("the template file is invalid: {}", error))?;
+            if merge_values(&mut value, added, "", &mut conflicts) {
+                Some(serde_yaml::to_string(&value).map_err(|error| error.to_string())?)
+            } else {
+                None
+            }
+        }
+    };
+
+    Ok((merged.unwrap_or_else(|| existing.to_string()), conflicts))
+}
+
+/// Returns the dotted path of a key inside the given table path.
+fn key_path(prefix: &str, key: &str) -> String {
+    if prefix.is_empty() {
+        key.to_string()
+    } else {
+        format!("{}.{}", prefix, key)
+    }
+}
+
+/// Returns the text of a TOML value without its surrounding whitespace and comments, to compare two values.
+fn plain_value(value: &Value) -> String {
+    let mut value = value.clone();
+    value.decor_mut().clear();
+    value.to_string()
+}
+
+/// Returns the text of a TOML item without its surrounding whitespace and comments, to compare two items.
+fn plain_item(item: &Item) -> String {
+    match item.as_value() {
+        Some(value) => plain_value(value),
+        None => item.to_string().trim().to_string(),
+    }
+}
+
+/// Merges a TOML table into an existing one and returns true if something was added.
+fn merge_toml(existing: &mut dyn TableLike, added: &dyn TableLike, prefix: &str, conflicts: &mut Vec<String>) -> bool {
+    let mut changed = false;
+
+    for (key, item) in added.iter() {
+        let path = key_path(prefix, key);
+
+        let current = match existing.get_mut(key) {
+            Some(current) => current,
+            None => {
+                existing.insert(key, item.clone());
+                changed = true;
+                continue;
+            }
+        };
+
+        if let (Some(current_table), Some(added_table)) = (current.as_table_like_mut(), item.as_table_like()) {
+            changed |= merge_toml(current_table, added_table, &path, conflicts);
+        } else if let (Some(current_array), Some(added_array)) = (current.as_array_mut(), item.as_array()) {
+            for value in added_array.iter() {
+                let mut value = value.clone();
+                value.decor_mut().clear();
+                if !current_array.iter().any(|current| plain_value(current) == plain_value(&value)) {
+                    current_array.push(value);
+                    changed = true;
+                }
+            }
+        } else if let (Some(current_tables), Some(added_tables)) = (current.as_array_of_tables_mut(), item.as_array_of_tables()) {
+            for table in added_tables.iter() {
+                let text = table.to_string();
+                if !current_tables.iter().any(|current| current.to_string() == text) {
+                    current_tables.push(table.clone());
+                    changed = true;
+                }
+            }
+        } else if plain_item(current) != plain_item(item) {
+            conflicts.push(path);
+        }
+    }
+
+    changed
+}
+
+/// Merges a JSON or YAML value into an existing one and returns true if something was added.
+fn merge_values(existing: &mut serde_json::Value, added: serde_json::Value, prefix: &str, conflicts: &mut Vec<String>) -> bool {
+    let mut changed = false;
+
+    match (existing, added) {
+        (serde_json::Value::Object(current), serde_json::Value::Object(added)) => {
+            for (key, value) in added {
+                let path = key_path(prefix, &key);
+                match current.get_mut(&key) {
+                    Some(current) => changed |= merge_values(current, value, &path, conflicts),
+                    None => {
+                        current.insert(key, value);
+                        changed = true;
+                    }
+                }
+            }
+        }
+        (serde_json::Value::Array(current), serde_json::Value::Array(added)) => {
+            for value in added {
+                if !current.contains(&value) {
+                    current.push(value);
+                    changed = true;
+                }
+            }
+        }
+        (current, added) => {
+            if *current != added {
+                conflicts.push(prefix.to_string());
+            }
+        }
+    }
+
+    changed
+}
+
+/// Merges the conflicting files of the plan that match the `merge` globs of the manifest into the existing files,
+/// instead of replacing them. A merged file is not a conflict anymore, its key conflicts are kept on the file.
+///
+/// # Arguments
+///
+/// * `plan`: &mut LoadPlan - The plan, with its actions resolved against the target directory
+/// * `target`: &Path - The directory the template is loaded into
+/// * `merged`: &GlobSet - The template paths of the files to merge
+///
+/// returns: Result<(), AppError>
+pub fn merge_files(plan: &mut LoadPlan, target: &Path, merged: &GlobSet) -> Result<(), AppError> {
+    for file in plan.files.iter_mut().filter(|file| file.conflict) {
+        let format = match file.source.as_ref().filter(|source| merged.is_match(source)) {
+            None => continue,
+            Some(_) => match StructuredFormat::from_path(&file.path) {
+                None => continue,
+                Some(format) => format,
+            },
+        };
+
+        let existing = std::fs::read_to_string(target.join(&file.path))?;
+        let (content, conflicts) = merge_content(format, &existing, &String::from_utf8_lossy(&file.content))
+            .map_err(|message| AppError::Merge {
+                file: file.path.display().to_string(),
+                message,
+            })?;
+
+        file.action = if content == existing { PlanAction::Unchanged } else { PlanAction::Merge };
+        file.conflict = false;
+        file.content = content.into_bytes();
+        file.permissions = None;
+        file.key_conflicts = conflicts;
+    }
+
+    Ok(())
+}
+
+/// Prints the files of a written plan that were merged and the keys that kept their existing value.
+///
+/// # Arguments
+///
+/// * `plan`: &LoadPlan - The written plan
+/// * `output`: W - Where the summary is written
+///
+/// returns: Result<(), AppError>
+pub fn print_merges<W: Write>(plan: &LoadPlan, mut output: W) -> Result<(), AppError> {
+    let merged: Vec<_> = plan
+        .files
+        .iter()
+        .filter(|file| file.action == PlanAction::Merge || !file.key_conflicts.is_empty())
+        .collect();
+    if merged.is_empty() {
+        return Ok(());
+    }
+
+    writeln!(output, "Merged files:")?;
+    for file in merged {
+        writeln!(output, "\t{}", file.path.display())?;
+        for key in &file.key_conflicts {
+            writeln!(output, "\t\tkept the existing value of `{}`", key)?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+    use crate::AppError;
+    use crate::conditions_module::build_glob_set;
+    use crate::merge_module::{merge_content, merge_files, print_merges, StructuredFormat};
+    use crate::plan_module::{LoadPlan, PlanAction};
+
+    #[test]
+    fn test_merge_toml() -> Result<(), String> {
+        let existing = "# My app\n[package]\nname = \"my-app\" # the name\nedition = \"2021\"\nkeywords = [\"cli\"]\n\n[dependencies]\nregex = \"1\"\n";
+        let template = "[package]\nname = \"my-app\"\nedition = \"2018\"\nkeywords = [\"cli\", \"template\"]\n\n[dependencies]\nserde = { version = \"1\", features = [\"derive\"] }\n";
+
+        let (merged, conflicts) = merge_content(StructuredFormat::Toml, existing, template)?;
+
+        assert_eq!(
+            merged,
+            "# My app\n[package]\nname = \"my-app\" # the name\nedition = \"2021\"\nkeywords = [\"cli\", \"template\"]\n\n\
+            [dependencies]\nregex = \"1\"\nserde = { version = \"1\", features = [\"derive\"] }\n"
+        );
+        assert_eq!(conflicts, vec!["package.edition"]);
+
+        let (again, _) = merge_content(StructuredFormat::Toml, &merged, template)?;
+        assert_eq!(again, merged);
+
+        assert!(merge_content(StructuredFormat::Toml, "[package", template).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_json_and_yaml() -> Result<(), String> {
+        let existing = "{\"name\": \"my-app\", \"scripts\": {\"test\": \"jest\"}, \"files\": [\"dist\"]}";
+        let template = "{\"name\": \"other\", \"scripts\": {\"build\": \"tsc\"}, \"files\": [\"dist\", \"types\"]}";
+
+        let (merged, conflicts) = merge_content(StructuredFormat::Json, existing, template)?;
+
+        assert_eq!(
+            merged,
+            "{\n  \"name\": \"my-app\",\n  \"scripts\": {\n    \"test\": \"jest\",\n    \"build\": \"tsc\"\n  },\n  \"files\": [\n    \"dist\",\n    \"types\"\n  ]\n}\n"
+        );
+        assert_eq!(conflicts, vec!["name"]);
+        assert_eq!(merge_content(StructuredFormat::Json, &merged, template)?.0, merged);
+
+        let existing = "on:\n  push:\n    branches: [main]\njobs:\n  test:\n    runs-on: ubuntu-latest\n";
+        let template = "on:\n  push:\n    branches: [main, develop]\njobs:\n  lint:\n    runs-on: ubuntu-latest\n";
+
+        let (merged, conflicts) = merge_content(StructuredFormat::Yaml, existing, template)?;
+
+        assert_eq!(
+            merged,
+            "on:\n  push:\n    branches:\n    - main\n    - develop\njobs:\n  test:\n    runs-on: ubuntu-latest\n  lint:\n    runs-on: ubuntu-latest\n"
+        );
+        assert!(conflicts.is_empty());
+
+        assert_eq!(StructuredFormat::from_path(Path::new("ci.yml")), Some(StructuredFormat::Yaml));
+        assert_eq!(StructuredFormat::from_path(Path::new("README.md")), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_merge_files() -> Result<(), AppError> {
+        let target = Path::new("/tmp/templater_merge_target/");
+        std::fs::create_dir_all(target)?;
+        std::fs::write(target.join("Cargo.toml"), "[package]\nname = \"old\"\n")?;
+        std::fs::write(target.join("config.json"), "{\"debug\": true}")?;
+
+        let mut plan = LoadPlan::default();
+        plan.add_file(PathBuf::from("Cargo.toml"), Some(PathBuf::from("Cargo.toml")), b"[package]\nname = \"new\"\n\n[dependencies]\n".to_vec(), None);
+        plan.add_file(PathBuf::from("config.json"), Some(PathBuf::from("config.json")), b"{\"debug\": false}".to_vec(), None);
+        plan.resolve_actions(target)?;
+
+        merge_files(&mut plan, target, &build_glob_set(["*.toml"])?)?;
+
+        assert_eq!(plan.files[0].action, PlanAction::Merge);
+        assert!(!plan.files[0].conflict);
+        assert_eq!(plan.files[0].key_conflicts, vec!["package.name"]);
+        assert_eq!(plan.files[1].action, PlanAction::Overwrite);
+        assert!(plan.files[1].conflict);
+
+        plan.write(target)?;
+        assert_eq!(std::fs::read_to_string(target.join("Cargo.toml"))?, "[package]\nname = \"old\"\n\n[dependencies]\n");
+
+        let mut output = Vec::new();
+        print_merges(&plan, &mut output)?;
+        assert_eq!(String::from_utf8(output).unwrap(), "Merged files:\n\tCargo.toml\n\t\tkept the existing value of `package.name`\n");
+
+        std::fs::write(target.join("config.json"), "{\"debug\": ")?;
+        assert!(matches!(merge_files(&mut plan, target, &build_glob_set(["*.json"])?), Err(AppError::Merge { .. })));
+
+        std::fs::remove_dir_all(target)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,306 @@
+use std::fs::Permissions;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use serde::Serialize;
+use crate::transaction_module::Transaction;
+use crate::AppError;
+
+/// What loading a template does with one path of the target directory.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum PlanAction {
+    /// The path does not exist yet and is created
+    Create,
+    /// The path exists with another content and is replaced
+    Overwrite,
+    /// The path already exists with the same content and is left as it is
+    Unchanged,
+    /// The path exists with another content and is kept because of the conflict policy
+    Skip,
+    /// The path exists with another content, it is moved to a backup and replaced
+    Backup,
+    /// The path exists with another content and the rendered content is merged into it
+    Merge,
+    /// The path exists and lines are inserted into it by an injection of the manifest
+    Inject,
+}
+
+impl std::fmt::Display for PlanAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let action = match self {
+            PlanAction::Create => "create",
+            PlanAction::Overwrite => "overwrite",
+            PlanAction::Unchanged => "unchanged",
+            PlanAction::Skip => "skip",
+            PlanAction::Backup => "backup",
+            PlanAction::Merge => "merge",
+            PlanAction::Inject => "inject",
+        };
+        f.pad(action)
+    }
+}
+
+/// A directory of the loaded project.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PlannedDirectory {
+    /// The rendered path, relative to the target directory
+    pub path: PathBuf,
+    pub action: PlanAction,
+}
+
+/// A file of the loaded project, rendered but not written yet.
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct PlannedFile {
+    /// The rendered path, relative to the target directory
+    pub path: PathBuf,
+    /// The path of the template file it comes from, relative to the template root, if any
+    pub source: Option<PathBuf>,
+    pub action: PlanAction,
+    /// True if the file already exists with another content
+    pub conflict: bool,
+    /// Where the existing file is moved before it is replaced, relative to the target directory
+    pub backup: Option<PathBuf>,
+    /// The keys of a merged file that kept their existing value
+    pub key_conflicts: Vec<String>,
+    #[serde(skip)]
+    pub content: Vec<u8>,
+    #[serde(skip)]
+    pub permissions: Option<Permissions>,
+}
+
+/// Everything loading a template writes into the target directory, in the order it is written.
+/// The plan is built from the template first, so it can be shown instead of written with `--dry-run`.
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct LoadPlan {
+    pub directories: Vec<PlannedDirectory>,
+    pub files: Vec<PlannedFile>,
+}
+
+impl LoadPlan {
+    /// Adds a directory to create, relative to the target directory.
+    pub fn add_directory(&mut self, path: PathBuf) {
+        self.directories.push(PlannedDirectory {
+            path,
+            action: PlanAction::Create,
+        });
+    }
+
+    /// Adds a file to write, relative to the target directory.
+    pub fn add_file(&mut self, path: PathBuf, source: Option<PathBuf>, content: Vec<u8>, permissions: Option<Permissions>) {
+        self.files.push(PlannedFile {
+            path,
+            source,
+            action: PlanAction::Create,
+            conflict: false,
+            backup: None,
+            key_conflicts: Vec::new(),
+            content,
+            permissions,
+        });
+    }
+
+    /// Compares the plan with the target directory and sets the action of every path:
+    /// missing paths are created, existing files with another content are overwritten and the others are unchanged.
+    /// A planned file that is a directory in the target, or the other way around, is an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `target`: &Path - The directory the template is loaded into
+    ///
+    /// returns: Result<(), AppError>
+    pub fn resolve_actions(&mut self, target: &Path) -> Result<(), AppError> {
+        for directory in &mut self.directories {
+            let path = target.join(&directory.path);
+            if path.exists() && !path.is_dir() {
+                return Err(kind_mismatch(&directory.path, "file", "directory"));
+            }
+            directory.action = if path.is_dir() {
+                PlanAction::Unchanged
+            } else {
+                PlanAction::Create
+            };
+        }
+
+        for file in &mut self.files {
+            let path = target.join(&file.path);
+            if path.is_dir() {
+                return Err(kind_mismatch(&file.path, "directory", "file"));
+            }
+            file.action = if !path.exists() {
+                PlanAction::Create
+            } else if std::fs::read(&path)? == file.content {
+                PlanAction::Unchanged
+            } else {
+                PlanAction::Overwrite
+            };
+            file.conflict = file.action == PlanAction::Overwrite;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the files that already exist in the target directory with another content.
+    pub fn conflicts(&self) -> impl Iterator<Item = &PlannedFile> {
+        self.files.iter().filter(|file| file.conflict)
+    }
+
+    /// Writes the directories and files of the plan into the target directory, leaving the unchanged and skipped
+    /// files alone. A file with a backup path is moved there before it is replaced.
+    /// The files are written in a transaction: if anything fails, or Ctrl-C is pressed once interrupts are watched,
+    /// the target directory is restored as it was.
+    ///
+    /// # Arguments
+    ///
+    /// * `target`: &Path - The directory the template is loaded into
+    ///
+    /// returns: Result<(), AppError>
+    pub fn write(&self, target: &Path) -> Result<(), AppError> {
+        let mut transaction = Transaction::new(target)?;
+
+        match self.write_in(&mut transaction) {
+            Ok(()) => transaction.commit(),
+            Err(error) => Err(transaction.rollback(error)),
+        }
+    }
+
+    fn write_in(&self, transaction: &mut Transaction) -> Result<(), AppError> {
+        let written: Vec<&PlannedFile> = self
+            .files
+            .iter()
+            .filter(|file| !matches!(file.action, PlanAction::Unchanged | PlanAction::Skip))
+            .collect();
+
+        for file in &written {
+            transaction.stage(&file.path, &file.content, file.permissions.as_ref())?;
+        }
+
+        for directory in &self.directories {
+            transaction.create_directory(&directory.path)?;
+        }
+
+        for file in &written {
+            if let Some(parent) = file.path.parent() {
+                transaction.create_directory(parent)?;
+            }
+            transaction.place(&file.path, file.backup.as_deref())?;
+        }
+
+        Ok(())
+    }
+
+    /// Prints the plan, one line per path that is created, overwritten or left unchanged.
+    /// A rendered path is followed by the template path it comes from.
+    ///
+    /// # Arguments
+    ///
+    /// * `output`: W - Where the plan is written
+    ///
+    /// returns: Result<(), AppError>
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// plan.resolve_actions(&target)?;
+    /// plan.print(std::io::stdout())?;
+    /// ```
+    pub fn print<W: Write>(&self, mut output: W) -> Result<(), AppError> {
+        for directory in self.directories.iter().filter(|directory| directory.action == PlanAction::Create) {
+            writeln!(output, "{:<10} {}/", directory.action, directory.path.display())?;
+        }
+
+        for file in &self.files {
+            write!(output, "{:<10} {}", file.action, file.path.display())?;
+            if let Some(source) = file.source.as_ref().filter(|source| **source != file.path) {
+                write!(output, " (from {})", source.display())?;
+            }
+            if let Some(backup) = &file.backup {
+                write!(output, " (backup: {})", backup.display())?;
+            }
+            if !file.key_conflicts.is_empty() {
+                write!(output, " (kept existing: {})", file.key_conflicts.join(", "))?;
+            }
+            writeln!(output)?;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the plan as JSON, for tools that read the output of `--dry-run --json`.
+    pub fn to_json(&self) -> Result<String, AppError> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+}
+
+fn kind_mismatch(path: &Path, existing: &str, planned: &str) -> AppError {
+    AppError::PathKindMismatch {
+        path: path.display().to_string(),
+        existing: existing.to_string(),
+        planned: planned.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::{Path, PathBuf};
+    use crate::AppError;
+    use crate::plan_module::{LoadPlan, PlanAction};
+
+    #[test]
+    fn test_load_plan() -> Result<(), AppError> {
+        let target = Path::new("/tmp/templater_plan_target/");
+        std::fs::create_dir_all(target.join("src"))?;
+        std::fs::write(target.join("Cargo.toml"), "name = \"old\"")?;
+        std::fs::write(target.join("README.md"), "# my-app")?;
+
+        let mut plan = LoadPlan::default();
+        plan.add_directory(PathBuf::from("src"));
+        plan.add_directory(PathBuf::from("src/handlers"));
+        plan.add_file(PathBuf::from("Cargo.toml"), Some(PathBuf::from("Cargo.toml")), b"name = \"my-app\"".to_vec(), None);
+        plan.add_file(PathBuf::from("README.md"), Some(PathBuf::from("README.md")), b"# my-app".to_vec(), None);
+        plan.add_file(
+            PathBuf::from("src/handlers/users.rs"),
+            Some(PathBuf::from("src/handlers/{{ item }}.rs")),
+            b"pub fn users() {}".to_vec(),
+            None,
+        );
+
+        plan.resolve_actions(target)?;
+
+        let actions: Vec<PlanAction> = plan.files.iter().map(|file| file.action).collect();
+        assert_eq!(actions, vec![PlanAction::Overwrite, PlanAction::Unchanged, PlanAction::Create]);
+        assert_eq!(plan.directories[0].action, PlanAction::Unchanged);
+        assert_eq!(plan.directories[1].action, PlanAction::Create);
+
+        let mut output = Vec::new();
+        plan.print(&mut output)?;
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "create     src/handlers/\noverwrite  Cargo.toml\nunchanged  README.md\ncreate     src/handlers/users.rs (from src/handlers/{{ item }}.rs)\n"
+        );
+
+        let json: serde_json::Value = serde_json::from_str(&plan.to_json()?)?;
+        assert_eq!(json["files"][2]["path"], "src/handlers/users.rs");
+        assert_eq!(json["files"][2]["action"], "create");
+        assert!(json["files"][2].get("content").is_none());
+
+        assert!(!target.join("src/handlers").exists());
+
+        plan.write(target)?;
+
+        assert_eq!(std::fs::read_to_string(target.join("Cargo.toml"))?, "name = \"my-app\"");
+        assert_eq!(std::fs::read_to_string(target.join("src/handlers/users.rs"))?, "pub fn users() {}");
+
+        let mut plan = LoadPlan::default();
+        plan.add_file(PathBuf::from("src"), Some(PathBuf::from("src")), b"not a directory".to_vec(), None);
+        assert!(matches!(plan.resolve_actions(target), Err(AppError::PathKindMismatch { .. })));
+
+        let mut plan = LoadPlan::default();
+        plan.add_directory(PathBuf::from("Cargo.toml"));
+        assert!(matches!(plan.resolve_actions(target), Err(AppError::PathKindMismatch { .. })));
+        assert!(target.join("src/handlers/users.rs").exists());
+
+        std::fs::remove_dir_all(target)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,213 @@
+use std::io::{BufRead, Write};
+use crate::conditions_module::{is_asked, skip_variable, undeclared_values};
+use crate::render_module::{value_to_string, Variables};
+use crate::template_manifest_module::TemplateVariable;
+use crate::validation_module::validate_value;
+use crate::AppError;
+
+/// Asks the user for the value of every declared variable that was not passed on the command line.
+/// The help text, the allowed choices and the default value are shown with each question; an empty answer keeps the default.
+/// Every answer is checked against the declaration of the variable and asked again until it is valid.
+/// A variable whose `when` condition does not hold for the earlier answers is not asked.
+///
+/// # Arguments
+///
+/// * `declared`: &[TemplateVariable] - The variables declared by the template manifest
+/// * `provided`: &Variables - The values already passed on the command line
+/// * `known`: &Variables - The resolved values; before the first question the `when` conditions only see the ones
+///   that are not declared, such as the built-in variables
+/// * `input`: R - Where the answers are read from
+/// * `output`: W - Where the questions are written to
+///
+/// returns: Result<Variables, AppError>
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use cli::prompt_module::prompt_variables;
+/// use cli::render_module::Variables;
+///
+/// let answers = prompt_variables(&manifest.variables, &Variables::new(), &builtins, std::io::stdin().lock(), std::io::stderr())?;
+/// ```
+pub fn prompt_variables<R: BufRead, W: Write>(
+    declared: &[TemplateVariable],
+    provided: &Variables,
+    known: &Variables,
+    mut input: R,
+    mut output: W,
+) -> Result<Variables, AppError> {
+    let mut answers = Variables::new();
+    let mut known = undeclared_values(declared, known);
+
+    for variable in declared {
+        if !is_asked(variable, &known)? {
+            skip_variable(variable, &mut known);
+            continue;
+        }
+
+        if let Some(value) = provided.get(&variable.name) {
+            known.insert(variable.name.clone(), validate_value(variable, value).unwrap_or_else(|_| value.clone()));
+            continue;
+        }
+
+        if let Some(help) = &variable.help {
+            writeln!(output, "{}", help)?;
+        }
+
+        let choices = if variable.choices.is_empty() {
+            String::new()
+        } else {
+            format!(" ({})", variable.choices.iter().map(value_to_string).collect::<Vec<_>>().join("/"))
+        };
+
+        loop {
+            match &variable.default {
+                None => write!(output, "{}{}: ", variable.name, choices)?,
+                Some(default) => write!(output, "{}{} [{}]: ", variable.name, choices, value_to_string(default))?,
+            }
+            output.flush()?;
+
+            let mut answer = String::new();
+            if input.read_line(&mut answer)? == 0 {
+                return Err(AppError::Io(std::io::Error::new(
+                    std::io::ErrorKind::UnexpectedEof,
+                    format!("No answer was given for the template variable `{}`.", variable.name),
+                )));
+            }
+
+            let answer = answer.trim();
+            let value = match (answer.is_empty(), &variable.default) {
+                (false, _) => toml::Value::from(answer),
+                (true, Some(default)) => default.clone(),
+                (true, None) => {
+                    writeln!(output, "A value is required for `{}`.", variable.name)?;
+                    continue;
+                }
+            };
+
+            match validate_value(variable, &value) {
+                Ok(value) => {
+                    known.insert(variable.name.clone(), value.clone());
+                    answers.insert(variable.name.clone(), value);
+                    break;
+                }
+                Err(reason) => writeln!(output, "Invalid value for `{}`: {}", variable.name, reason)?,
+            }
+        }
+    }
+
+    Ok(answers)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::AppError;
+    use crate::prompt_module::prompt_variables;
+    use crate::render_module::Variables;
+    use crate::template_manifest_module::{TemplateVariable, VariableType};
+
+    fn test_declared() -> Vec<TemplateVariable> {
+        vec![
+            TemplateVariable {
+                name: "project_name".to_string(),
+                default: None,
+                help: Some("Name of the generated project".to_string()),
+                ..Default::default()
+            },
+            TemplateVariable {
+                name: "license".to_string(),
+                default: Some(toml::Value::from("MIT")),
+                help: None,
+                ..Default::default()
+            },
+            TemplateVariable {
+                name: "crate_name".to_string(),
+                default: None,
+                help: None,
+                ..Default::default()
+            },
+            TemplateVariable {
+                name: "workers".to_string(),
+                default: Some(toml::Value::from(2)),
+                variable_type: VariableType::Integer,
+                min: Some(1),
+                ..Default::default()
+            },
+        ]
+    }
+
+    #[test]
+    fn test_prompt_variables() -> Result<(), AppError> {
+        let mut provided = Variables::new();
+        provided.insert("crate_name".to_string(), toml::Value::from("my_app"));
+        let mut output = Vec::new();
+
+        let answers = prompt_variables(&test_declared(), &provided, &provided, "\n  my-app \n\n0\n3\n".as_bytes(), &mut output)?;
+        let output = String::from_utf8(output).unwrap();
+
+        assert_eq!(answers.len(), 3);
+        assert_eq!(answers["workers"], toml::Value::from(3));
+        assert!(output.contains("Invalid value for `workers`"));
+        assert_eq!(answers["project_name"], toml::Value::from("my-app"));
+        assert_eq!(answers["license"], toml::Value::from("MIT"));
+        assert!(output.contains("Name of the generated project"));
+        assert!(output.contains("A value is required for `project_name`."));
+        assert!(output.contains("license [MIT]: "));
+        assert!(!output.contains("crate_name"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_prompt_variables_end_of_input() {
+        let result = prompt_variables(&test_declared(), &Variables::new(), &Variables::new(), "".as_bytes(), Vec::new());
+
+        assert!(matches!(result, Err(AppError::Io(_))));
+    }
+
+    #[test]
+    fn test_prompt_variables_when() -> Result<(), AppError> {
+        let declared = vec![
+            TemplateVariable {
+                name: "database".to_string(),
+                variable_type: VariableType::Bool,
+                ..Default::default()
+            },
+            TemplateVariable {
+                name: "db_url".to_string(),
+                when: Some("database".to_string()),
+                ..Default::default()
+            },
+            TemplateVariable {
+                name: "pool_size".to_string(),
+                default: Some(toml::Value::from(4)),
+                variable_type: VariableType::Integer,
+                when: Some("database and db_url is startingwith('postgres')".to_string()),
+                ..Default::default()
+            },
+        ];
+        let mut output = Vec::new();
+
+        let answers = prompt_variables(&declared, &Variables::new(), &Variables::new(), "no\n".as_bytes(), &mut output)?;
+        let output = String::from_utf8(output).unwrap();
+
+        assert_eq!(answers.len(), 1);
+        assert_eq!(answers["database"], toml::Value::from(false));
+        assert!(!output.contains("db_url"));
+
+        let answers = prompt_variables(&declared, &Variables::new(), &Variables::new(), "yes\npostgres://db\n8\n".as_bytes(), Vec::new())?;
+
+        assert_eq!(answers["db_url"], toml::Value::from("postgres://db"));
+        assert_eq!(answers["pool_size"], toml::Value::from(8));
+
+        let mut provided = Variables::new();
+        provided.insert("database".to_string(), toml::Value::from("true"));
+        provided.insert("db_url".to_string(), toml::Value::from("sqlite://db"));
+
+        let answers = prompt_variables(&declared, &provided, &provided, "".as_bytes(), Vec::new())?;
+
+        assert!(answers.is_empty());
+
+        Ok(())
+    }
+}
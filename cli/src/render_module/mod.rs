@@ -0,0 +1,182 @@
+use std::collections::BTreeMap;
+use std::ffi::{OsStr, OsString};
+use crate::template_engine_module::TemplateEngine;
+use crate::AppError;
+
+/// Variables available to a template while it is loaded, keyed by placeholder name.
+pub type Variables = BTreeMap<String, toml::Value>;
+
+/// How many leading bytes are inspected when deciding if a file is binary.
+const BINARY_SNIFF_LENGTH: usize = 8000;
+
+/// Returns true if the given content should be copied verbatim instead of rendered.
+/// A file is treated as binary if it contains a NUL byte near the start or is not valid UTF-8.
+///
+/// # Arguments
+///
+/// * `content`: &[u8] - The raw content of a stored template file
+///
+/// returns: bool
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use cli::render_module::is_binary;
+///
+/// assert!(!is_binary(b"name = \"{{project_name}}\""));
+/// assert!(is_binary(&[0x89, 0x50, 0x4e, 0x47, 0x00]));
+/// ```
+pub fn is_binary(content: &[u8]) -> bool {
+    content.iter().take(BINARY_SNIFF_LENGTH).any(|&byte| byte == 0) || std::str::from_utf8(content).is_err()
+}
+
+/// Formats a variable value for display and comparison.
+/// Strings are written without quotes, every other value uses its TOML representation.
+///
+/// # Arguments
+///
+/// * `value`: &toml::Value - The value of a template variable
+///
+/// returns: String
+pub fn value_to_string(value: &toml::Value) -> String {
+    match value {
+        toml::Value::String(text) => text.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// Renders the content of a stored template file. Binary content is returned byte-for-byte.
+///
+/// # Arguments
+///
+/// * `engine`: &TemplateEngine - The engine holding the template variables
+/// * `name`: &str - The path of the file relative to the template root, used in error messages
+/// * `content`: Vec<u8> - The raw content of a stored template file
+///
+/// returns: Result<Vec<u8>, AppError>
+pub fn render_file_content(engine: &TemplateEngine, name: &str, content: Vec<u8>) -> Result<Vec<u8>, AppError> {
+    if is_binary(&content) {
+        return Ok(content);
+    }
+
+    match String::from_utf8(content) {
+        Ok(text) => Ok(engine.render_str(name, &text)?.into_bytes()),
+        Err(error) => Ok(error.into_bytes()),
+    }
+}
+
+/// Renders a single file or directory name. Names that are not valid UTF-8 are returned unchanged.
+///
+/// # Arguments
+///
+/// * `engine`: &TemplateEngine - The engine holding the template variables
+/// * `name`: &OsStr - The stored name of a file or directory inside the template
+///
+/// returns: Result<OsString, AppError>
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use std::ffi::OsStr;
+/// use std::path::Path;
+/// use cli::render_module::{render_file_name, Variables};
+/// use cli::template_engine_module::{TemplateEngine, TemplateSyntax};
+///
+/// let mut variables = Variables::new();
+/// variables.insert("project_name".to_string(), toml::Value::from("app"));
+/// let engine = TemplateEngine::new(Path::new("/tmp/app/templater/templates/test"), &variables, &TemplateSyntax::default())?;
+///
+/// assert_eq!(render_file_name(&engine, OsStr::new("{{project_name}}.service"))?, "app.service");
+/// ```
+pub fn render_file_name(engine: &TemplateEngine, name: &OsStr) -> Result<OsString, AppError> {
+    let template_name = match name.to_str() {
+        None => return Ok(name.to_os_string()),
+        Some(template_name) => template_name,
+    };
+
+    let rendered_name = engine.render_str(template_name, template_name)?;
+
+    if rendered_name.trim().is_empty() {
+        return Err(AppError::EmptyRenderedName(template_name.to_string()));
+    }
+
+    if rendered_name.contains('/') || rendered_name.contains(std::path::MAIN_SEPARATOR) {
+        return Err(AppError::RenderedNameContainsSeparator {
+            template_name: template_name.to_string(),
+            rendered_name,
+        });
+    }
+
+    if rendered_name == "." || rendered_name == ".." {
+        return Err(AppError::RenderedNameIsRelative {
+            template_name: template_name.to_string(),
+            rendered_name,
+        });
+    }
+
+    Ok(OsString::from(rendered_name))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsStr;
+    use std::path::Path;
+    use crate::AppError;
+    use crate::render_module::{is_binary, render_file_content, render_file_name, value_to_string, Variables};
+    use crate::template_engine_module::{TemplateEngine, TemplateSyntax};
+
+    fn test_engine() -> TemplateEngine {
+        let mut variables = Variables::new();
+        variables.insert("project_name".to_string(), toml::Value::from("my-app"));
+        variables.insert("empty".to_string(), toml::Value::from(""));
+        variables.insert("nested".to_string(), toml::Value::from("a/b"));
+        variables.insert("current".to_string(), toml::Value::from("."));
+        variables.insert("parent".to_string(), toml::Value::from(".."));
+        TemplateEngine::new(Path::new("/tmp"), &variables, &TemplateSyntax::default()).unwrap()
+    }
+
+    #[test]
+    fn test_value_to_string() {
+        assert_eq!(value_to_string(&toml::Value::from("my-app")), "my-app");
+        assert_eq!(value_to_string(&toml::Value::from(4)), "4");
+        assert_eq!(value_to_string(&toml::Value::from(true)), "true");
+    }
+
+    #[test]
+    fn test_render_file_content() -> Result<(), AppError> {
+        let engine = test_engine();
+        let binary = vec![0x89, 0x50, 0x4e, 0x47, 0x00, b'{', b'{', b'}', b'}'];
+        let invalid_utf8 = vec![0xff, 0xfe, b'{', b'{', b'p', b'}', b'}'];
+
+        assert!(is_binary(&binary));
+        assert!(is_binary(&invalid_utf8));
+        assert_eq!(render_file_content(&engine, "logo.png", binary.clone())?, binary);
+        assert_eq!(render_file_content(&engine, "data.bin", invalid_utf8.clone())?, invalid_utf8);
+        assert_eq!(render_file_content(&engine, "Cargo.toml", b"name = \"{{ project_name }}\"\n".to_vec())?, b"name = \"my-app\"\n");
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_file_name() -> Result<(), AppError> {
+        let engine = test_engine();
+
+        assert_eq!(render_file_name(&engine, OsStr::new("{{project_name}}.service"))?, "my-app.service");
+        assert_eq!(render_file_name(&engine, OsStr::new("mod.rs"))?, "mod.rs");
+        assert!(matches!(render_file_name(&engine, OsStr::new("{{empty}}")), Err(AppError::EmptyRenderedName(_))));
+        assert!(matches!(
+            render_file_name(&engine, OsStr::new("{{nested}}.rs")),
+            Err(AppError::RenderedNameContainsSeparator { .. })
+        ));
+        assert!(matches!(
+            render_file_name(&engine, OsStr::new("{{current}}")),
+            Err(AppError::RenderedNameIsRelative { .. })
+        ));
+        assert!(matches!(
+            render_file_name(&engine, OsStr::new("{{parent}}")),
+            Err(AppError::RenderedNameIsRelative { .. })
+        ));
+
+        Ok(())
+    }
+}
@@ -0,0 +1,120 @@
+use std::path::Path;
+use globset::GlobSet;
+use serde::{Deserialize, Serialize};
+use crate::conditions_module::build_glob_set;
+use crate::render_module::Variables;
+use crate::AppError;
+
+/// Paths of a template that are loaded once for every item of a list variable.
+/// The current item is available to the file names and contents under the name `item` (or the name set with `item`).
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RepeatedPaths {
+    pub paths: Vec<String>,
+    pub variable: String,
+    #[serde(default = "default_item_name")]
+    pub item: String,
+}
+
+fn default_item_name() -> String {
+    "item".to_string()
+}
+
+/// The paths of a `RepeatedPaths` entry together with the items they are loaded for.
+pub struct Repetition {
+    matcher: GlobSet,
+    pub item: String,
+    pub items: Vec<toml::Value>,
+}
+
+impl Repetition {
+    /// Returns true if the given path, relative to the template root, is loaded once per item.
+    pub fn matches(&self, relative_path: &Path) -> bool {
+        self.matcher.is_match(relative_path)
+    }
+}
+
+/// Resolves the items of every repeated path. An undefined variable repeats the paths zero times,
+/// a variable that is not a list is an error.
+///
+/// # Arguments
+///
+/// * `repeated_paths`: &[RepeatedPaths] - The repeated paths declared by the template manifest
+/// * `variables`: &Variables - The resolved variables
+///
+/// returns: Result<Vec<Repetition>, AppError>
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use std::path::Path;
+/// use cli::repeat_module::repetitions;
+///
+/// let repetitions = repetitions(&manifest.repeated_paths, &variables)?;
+///
+/// if repetitions[0].matches(Path::new("src/handlers/{{ item }}.rs")) {
+///     println!("One handler is loaded for each of {} items.", repetitions[0].items.len());
+/// }
+/// ```
+pub fn repetitions(repeated_paths: &[RepeatedPaths], variables: &Variables) -> Result<Vec<Repetition>, AppError> {
+    repeated_paths
+        .iter()
+        .map(|repeated| {
+            let items = match variables.get(&repeated.variable) {
+                None => Vec::new(),
+                Some(toml::Value::Array(items)) => items.clone(),
+                Some(_) => return Err(AppError::RepeatedVariableNotList(repeated.variable.clone())),
+            };
+
+            Ok(Repetition {
+                matcher: build_glob_set(&repeated.paths)?,
+                item: repeated.item.clone(),
+                items,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::AppError;
+    use crate::render_module::Variables;
+    use crate::repeat_module::{repetitions, RepeatedPaths};
+
+    #[test]
+    fn test_repetitions() -> Result<(), AppError> {
+        let repeated_paths = vec![
+            RepeatedPaths {
+                paths: vec!["src/handlers/*.rs".to_string()],
+                variable: "handlers".to_string(),
+                item: "item".to_string(),
+            },
+            RepeatedPaths {
+                paths: vec!["services/*".to_string()],
+                variable: "services".to_string(),
+                item: "service".to_string(),
+            },
+        ];
+
+        let mut variables = Variables::new();
+        variables.insert("handlers".to_string(), toml::Value::from(vec!["users", "orders"]));
+
+        let resolved = repetitions(&repeated_paths, &variables)?;
+
+        assert!(resolved[0].matches(Path::new("src/handlers/{{ item }}.rs")));
+        assert!(!resolved[0].matches(Path::new("src/main.rs")));
+        assert_eq!(resolved[0].items, vec![toml::Value::from("users"), toml::Value::from("orders")]);
+        assert_eq!(resolved[1].item, "service");
+        assert!(resolved[1].items.is_empty());
+
+        variables.insert("services".to_string(), toml::Value::from("api"));
+
+        assert!(matches!(
+            repetitions(&repeated_paths, &variables),
+            Err(AppError::RepeatedVariableNotList(variable)) if variable == "services"
+        ));
+
+        Ok(())
+    }
+}
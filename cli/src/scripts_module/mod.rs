@@ -0,0 +1,252 @@
+use std::path::{Component, Path, PathBuf};
+use rhai::module_resolvers::DummyModuleResolver;
+use rhai::{Dynamic, Engine, EvalAltResult, Scope};
+use serde::{Deserialize, Serialize};
+use crate::render_module::Variables;
+use crate::AppError;
+
+/// The most operations a script may run, so a script that never ends stops with an error.
+const MAX_OPERATIONS: u64 = 10_000_000;
+
+/// The Rhai scripts a template runs around loading, set in the `[scripts]` table of the manifest.
+/// Paths are relative to the template root and the scripts are not copied into the loaded project.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateScripts {
+    /// Scripts run before any file is written. A script can return a map of variables to add or replace.
+    #[serde(default)]
+    pub pre_load: Vec<String>,
+    /// Scripts run after the template is loaded, before the hooks
+    #[serde(default)]
+    pub post_load: Vec<String>,
+}
+
+impl TemplateScripts {
+    /// Returns the paths of every script.
+    pub fn paths(&self) -> impl Iterator<Item = &String> {
+        self.pre_load.iter().chain(&self.post_load)
+    }
+}
+
+/// Runs the scripts of a template in a sandbox. Scripts can read the resolved variables through the `variables` map,
+/// and can only touch files inside the target directory with `read_file`, `write_file`, `file_exists`, `create_dir`
+/// and `remove_file`, which take paths relative to it. Modules cannot be imported and the number of operations is limited.
+/// A read-only runner, used for the pre-load scripts, fails on the functions that change files:
+/// files are only changed by the plan of `load-template`, which checks the conflicts and can roll back.
+pub struct ScriptRunner {
+    engine: Engine,
+    template_root: PathBuf,
+}
+
+impl ScriptRunner {
+    /// Creates a script runner for a template.
+    ///
+    /// # Arguments
+    ///
+    /// * `template_root`: &Path - The root directory of the template, the script paths are relative to it
+    /// * `target`: &Path - The directory the template is loaded into, the only directory scripts can access
+    /// * `writable`: bool - Whether scripts may change files in the target directory
+    ///
+    /// returns: Result<ScriptRunner, AppError>
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use std::path::Path;
+    /// use cli::scripts_module::ScriptRunner;
+    ///
+    /// let runner = ScriptRunner::new(Path::new("/tmp/app/templater/templates/test"), Path::new("/home/user/my-app"), true)?;
+    /// let computed = runner.compute("scripts/variables.rhai", &variables)?;
+    /// ```
+    pub fn new(template_root: &Path, target: &Path, writable: bool) -> Result<Self, AppError> {
+        let target = target.canonicalize()?;
+
+        let mut engine = Engine::new();
+        engine.set_module_resolver(DummyModuleResolver::new());
+        engine.disable_symbol("eval");
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_call_levels(64);
+        // Standard output is kept for the plan of `--dry-run --json`
+        engine.on_print(|text| eprintln!("{}", text));
+
+        let root = target.clone();
+        engine.register_fn("read_file", move |path: &str| -> Result<String, Box<EvalAltResult>> {
+            std::fs::read_to_string(scoped_path(&root, path)?).map_err(|error| script_io_error(path, error))
+        });
+
+        let root = target.clone();
+        engine.register_fn("write_file", move |path: &str, content: &str| -> Result<(), Box<EvalAltResult>> {
+            let path_in_target = scoped_path(&root, path)?;
+            if !writable {
+                return Err(read_only(path));
+            }
+            if let Some(parent) = path_in_target.parent() {
+                std::fs::create_dir_all(parent).map_err(|error| script_io_error(path, error))?;
+            }
+            std::fs::write(path_in_target, content).map_err(|error| script_io_error(path, error))
+        });
+
+        let root = target.clone();
+        engine.register_fn("file_exists", move |path: &str| -> Result<bool, Box<EvalAltResult>> {
+            Ok(scoped_path(&root, path)?.exists())
+        });
+
+        let root = target.clone();
+        engine.register_fn("create_dir", move |path: &str| -> Result<(), Box<EvalAltResult>> {
+            let path_in_target = scoped_path(&root, path)?;
+            if !writable {
+                return Err(read_only(path));
+            }
+            std::fs::create_dir_all(path_in_target).map_err(|error| script_io_error(path, error))
+        });
+
+        let root = target;
+        engine.register_fn("remove_file", move |path: &str| -> Result<(), Box<EvalAltResult>> {
+            let path_in_target = scoped_path(&root, path)?;
+            if !writable {
+                return Err(read_only(path));
+            }
+            std::fs::remove_file(path_in_target).map_err(|error| script_io_error(path, error))
+        });
+
+        Ok(Self {
+            engine,
+            template_root: template_root.to_path_buf(),
+        })
+    }
+
+    /// Runs a script of the template and returns the variables it computed.
+    /// A script that ends with a map returns its entries, a script that ends with no value returns no variables.
+    ///
+    /// # Arguments
+    ///
+    /// * `script`: &str - The path of the script, relative to the template root
+    /// * `variables`: &Variables - The resolved variables, readable as `variables`
+    ///
+    /// returns: Result<Variables, AppError>
+    pub fn compute(&self, script: &str, variables: &Variables) -> Result<Variables, AppError> {
+        let result = self.evaluate(script, variables)?;
+
+        if result.is_unit() {
+            return Ok(Variables::new());
+        }
+        if !result.is_map() {
+            return Err(script_error(script, format!("the script must end with a map of variables or no value, not a {}", result.type_name())));
+        }
+
+        rhai::serde::from_dynamic(&result).map_err(|error| script_error(script, error.to_string()))
+    }
+
+    /// Runs a script of the template, ignoring the value it ends with.
+    ///
+    /// # Arguments
+    ///
+    /// * `script`: &str - The path of the script, relative to the template root
+    /// * `variables`: &Variables - The resolved variables, readable as `variables`
+    ///
+    /// returns: Result<(), AppError>
+    pub fn run(&self, script: &str, variables: &Variables) -> Result<(), AppError> {
+        self.evaluate(script, variables).map(|_| ())
+    }
+
+    fn evaluate(&self, script: &str, variables: &Variables) -> Result<Dynamic, AppError> {
+        let source = std::fs::read_to_string(self.template_root.join(script))?;
+
+        let mut scope = Scope::new();
+        scope.push_constant("variables", rhai::serde::to_dynamic(variables).map_err(|error| script_error(script, error.to_string()))?);
+
+        self.engine
+            .eval_with_scope(&mut scope, &source)
+            .map_err(|error| script_error(script, error.to_string()))
+    }
+}
+
+fn script_error(script: &str, message: String) -> AppError {
+    AppError::Script {
+        script: script.to_string(),
+        message,
+    }
+}
+
+/// Resolves a path given by a script against the target directory, refusing paths that lead out of it.
+fn scoped_path(target: &Path, path: &str) -> Result<PathBuf, Box<EvalAltResult>> {
+    let outside = || format!("`{}` is outside the target directory", path).into();
+
+    if Path::new(path).components().any(|component| !matches!(component, Component::Normal(_) | Component::CurDir)) {
+        return Err(outside());
+    }
+
+    let path_in_target = target.join(path);
+
+    // A symbolic link inside the target must not lead out of it either
+    let existing = path_in_target.ancestors().find(|ancestor| ancestor.exists()).unwrap_or(target);
+    match existing.canonicalize() {
+        Ok(existing) if existing.starts_with(target) => Ok(path_in_target),
+        _ => Err(outside()),
+    }
+}
+
+fn read_only(path: &str) -> Box<EvalAltResult> {
+    format!("`{}` cannot be changed, only post-load scripts can change files", path).into()
+}
+
+fn script_io_error(path: &str, error: std::io::Error) -> Box<EvalAltResult> {
+    format!("`{}`: {}", path, error).into()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::AppError;
+    use crate::render_module::Variables;
+    use crate::scripts_module::ScriptRunner;
+
+    #[test]
+    fn test_run_scripts() -> Result<(), AppError> {
+        let template_root = Path::new("/tmp/templater_scripts_template/");
+        let target = Path::new("/tmp/templater_scripts_target/");
+        std::fs::create_dir_all(template_root.join("scripts"))?;
+        std::fs::create_dir_all(target)?;
+
+        std::fs::write(
+            template_root.join("scripts/variables.rhai"),
+            "let ports = [];\nfor i in 0..variables.services { ports.push(8000 + i); }\n#{ slug: variables.project_name.to_lower(), ports: ports }",
+        )?;
+        std::fs::write(
+            template_root.join("scripts/finish.rhai"),
+            "write_file(\"notes/NEXT.md\", `Run ${variables.project_name}`);\nif !file_exists(\"notes/NEXT.md\") { throw \"missing\"; }\nread_file(\"notes/NEXT.md\");",
+        )?;
+        std::fs::write(template_root.join("scripts/escape.rhai"), "write_file(\"../escaped.txt\", \"\")")?;
+        std::fs::write(template_root.join("scripts/loop.rhai"), "loop { }")?;
+        std::fs::write(template_root.join("scripts/import.rhai"), "import \"other\" as other;")?;
+        std::fs::write(template_root.join("scripts/number.rhai"), "42")?;
+
+        let mut variables = Variables::new();
+        variables.insert("project_name".to_string(), toml::Value::from("My-App"));
+        variables.insert("services".to_string(), toml::Value::from(2));
+
+        let runner = ScriptRunner::new(template_root, target, true)?;
+
+        let computed = runner.compute("scripts/variables.rhai", &variables)?;
+        assert_eq!(computed["slug"], toml::Value::from("my-app"));
+        assert_eq!(computed["ports"], toml::Value::from(vec![8000, 8001]));
+
+        runner.run("scripts/finish.rhai", &variables)?;
+        assert_eq!(std::fs::read_to_string(target.join("notes/NEXT.md"))?, "Run My-App");
+
+        for script in ["scripts/escape.rhai", "scripts/loop.rhai", "scripts/import.rhai", "scripts/number.rhai"] {
+            assert!(matches!(runner.compute(script, &variables), Err(AppError::Script { .. })), "{} should fail", script);
+        }
+        assert!(!Path::new("/tmp/escaped.txt").exists());
+
+        std::fs::remove_dir_all(target.join("notes"))?;
+        let read_only = ScriptRunner::new(template_root, target, false)?;
+        assert!(matches!(read_only.run("scripts/finish.rhai", &variables), Err(AppError::Script { .. })));
+        assert!(!target.join("notes").exists());
+
+        std::fs::remove_dir_all(template_root)?;
+        std::fs::remove_dir_all(target)?;
+
+        Ok(())
+    }
+}
@@ -10,7 +10,10 @@ pub struct InitialConfig {
     pub version: f32,
     pub template_absolute_path: PathBuf,
     pub initialized: bool,
-    pub templates: Vec<String>
+    pub templates: Vec<String>,
+    /// Commands run on a staging copy of a directory before it is saved as a template
+    #[serde(default)]
+    pub pre_save_hooks: Vec<String>
 }
 
 impl InitialConfig {
@@ -54,7 +57,8 @@ impl InitialConfig {
             version,
             template_absolute_path,
             initialized: false,
-            templates: Vec::new()
+            templates: Vec::new(),
+            pre_save_hooks: Vec::new()
         })
     }
 
@@ -97,7 +101,8 @@ impl InitialConfig {
             version: app_version!(),
             template_absolute_path: home_dir,
             initialized: false,
-            templates: Vec::new()
+            templates: Vec::new(),
+            pre_save_hooks: Vec::new()
         })
     }
 }
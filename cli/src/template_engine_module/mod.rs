@@ -0,0 +1,310 @@
+use std::collections::HashSet;
+use std::path::Path;
+use minijinja::syntax::SyntaxConfig;
+use minijinja::{Environment, UndefinedBehavior, Value};
+use serde::{Deserialize, Serialize};
+use crate::filters_module::add_filters;
+use crate::render_module::Variables;
+use crate::AppError;
+
+/// The delimiters of the template language, set in the `[syntax]` table of the manifest.
+/// Every missing delimiter keeps its default: `{{ }}`, `{% %}` and `{# #}`.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateSyntax {
+    pub variable_start: Option<String>,
+    pub variable_end: Option<String>,
+    pub block_start: Option<String>,
+    pub block_end: Option<String>,
+    pub comment_start: Option<String>,
+    pub comment_end: Option<String>,
+}
+
+impl TemplateSyntax {
+    fn to_syntax_config(&self) -> Result<SyntaxConfig, AppError> {
+        fn delimiter(value: &Option<String>, default: &str) -> String {
+            value.clone().unwrap_or_else(|| default.to_string())
+        }
+
+        SyntaxConfig::builder()
+            .variable_delimiters(delimiter(&self.variable_start, "{{"), delimiter(&self.variable_end, "}}"))
+            .block_delimiters(delimiter(&self.block_start, "{%"), delimiter(&self.block_end, "%}"))
+            .comment_delimiters(delimiter(&self.comment_start, "{#"), delimiter(&self.comment_end, "#}"))
+            .build()
+            .map_err(|error| AppError::InvalidSyntax(error.to_string()))
+    }
+}
+
+/// Renders the files of a template with the Jinja template language.
+/// Text files can use `{% if %}` and `{% for %}` blocks, filters (including the case conversions of `filters_module`),
+/// `{% raw %}` blocks and `{% include "path" %}`, where included paths are relative to the template root.
+pub struct TemplateEngine {
+    environment: Environment<'static>,
+    context: Value,
+}
+
+impl TemplateEngine {
+    /// Creates a template engine that renders with the given variables.
+    ///
+    /// # Arguments
+    ///
+    /// * `template_root`: &Path - The root directory of the template, used to resolve includes
+    /// * `variables`: &Variables - The values available inside the templates
+    /// * `syntax`: &TemplateSyntax - The delimiters of the template language
+    ///
+    /// returns: Result<TemplateEngine, AppError>
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use std::path::Path;
+    /// use cli::render_module::Variables;
+    /// use cli::template_engine_module::{TemplateEngine, TemplateSyntax};
+    ///
+    /// let mut variables = Variables::new();
+    /// variables.insert("project_name".to_string(), toml::Value::from("app"));
+    ///
+    /// let engine = TemplateEngine::new(Path::new("/tmp/app/templater/templates/test"), &variables, &TemplateSyntax::default())?;
+    ///
+    /// assert_eq!(engine.render_str("Cargo.toml", "name = \"{{ project_name }}\"")?, "name = \"app\"");
+    /// ```
+    pub fn new(template_root: &Path, variables: &Variables, syntax: &TemplateSyntax) -> Result<Self, AppError> {
+        let mut environment = base_environment();
+        environment.set_syntax(syntax.to_syntax_config()?);
+        environment.set_keep_trailing_newline(true);
+        environment.set_loader(minijinja::path_loader(template_root));
+
+        Ok(Self {
+            environment,
+            context: Value::from_serialize(variables),
+        })
+    }
+
+    /// Returns an engine that renders with the same variables and one more, used for the items of repeated paths.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: &str - The name of the added variable
+    /// * `value`: &toml::Value - Its value, which hides a variable of the same name
+    ///
+    /// returns: TemplateEngine
+    pub fn with_variable(&self, name: &str, value: &toml::Value) -> Self {
+        let mut added = Variables::new();
+        added.insert(name.to_string(), value.clone());
+
+        Self {
+            environment: self.environment.clone(),
+            context: minijinja::context! { ..Value::from_serialize(&added), ..self.context.clone() },
+        }
+    }
+
+    /// Renders the given text.
+    ///
+    /// # Arguments
+    ///
+    /// * `name`: &str - The path of the rendered file relative to the template root, used in error messages
+    /// * `content`: &str - The text to render
+    ///
+    /// returns: Result<String, AppError>
+    pub fn render_str(&self, name: &str, content: &str) -> Result<String, AppError> {
+        self.environment
+            .render_named_str(name, content, &self.context)
+            .map_err(|error| render_error(name, error))
+    }
+}
+
+/// Creates an environment with the settings and filters shared by template files and manifest expressions.
+fn base_environment() -> Environment<'static> {
+    let mut environment = Environment::new();
+    environment.set_undefined_behavior(UndefinedBehavior::Strict);
+    add_filters(&mut environment);
+    environment
+}
+
+/// Evaluates an expression of the template language, such as `crate_name | snake_case` or `backend != "none"`.
+///
+/// # Arguments
+///
+/// * `expression`: &str - The expression to evaluate
+/// * `variables`: &Variables - The values the expression can read
+///
+/// returns: Result<Value, AppError>
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use cli::render_module::Variables;
+/// use cli::template_engine_module::evaluate_expression;
+///
+/// let mut variables = Variables::new();
+/// variables.insert("backend".to_string(), toml::Value::from("postgres"));
+///
+/// assert!(evaluate_expression("backend != 'none'", &variables)?.is_true());
+/// ```
+pub fn evaluate_expression(expression: &str, variables: &Variables) -> Result<Value, AppError> {
+    let environment = base_environment();
+
+    environment
+        .compile_expression(expression)
+        .and_then(|compiled| compiled.eval(Value::from_serialize(variables)))
+        .map_err(|error| expression_error(expression, error))
+}
+
+/// Returns the names of the variables an expression reads.
+///
+/// # Arguments
+///
+/// * `expression`: &str - The expression to inspect
+///
+/// returns: Result<HashSet<String>, AppError>
+pub fn expression_variables(expression: &str) -> Result<HashSet<String>, AppError> {
+    let environment = base_environment();
+
+    environment
+        .compile_expression(expression)
+        .map(|compiled| compiled.undeclared_variables(false))
+        .map_err(|error| expression_error(expression, error))
+}
+
+fn expression_error(expression: &str, error: minijinja::Error) -> AppError {
+    AppError::Expression {
+        expression: expression.to_string(),
+        message: match error.detail() {
+            None => error.kind().to_string(),
+            Some(detail) => format!("{}: {}", error.kind(), detail),
+        },
+    }
+}
+
+/// Converts an error of the template language into an `AppError` that names the template file and line.
+fn render_error(name: &str, error: minijinja::Error) -> AppError {
+    let message = match error.detail() {
+        None => error.kind().to_string(),
+        Some(detail) => format!("{}: {}", error.kind(), detail),
+    };
+
+    AppError::Render {
+        file: error.name().unwrap_or(name).to_string(),
+        line: error.line().unwrap_or(0),
+        message,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::AppError;
+    use crate::render_module::Variables;
+    use crate::template_engine_module::{evaluate_expression, expression_variables, TemplateEngine, TemplateSyntax};
+
+    fn test_variables() -> Variables {
+        let mut variables = Variables::new();
+        variables.insert("project_name".to_string(), toml::Value::from("my-app"));
+        variables.insert("serde".to_string(), toml::Value::from(true));
+        variables.insert("features".to_string(), toml::Value::from(vec!["cli", "json"]));
+        variables
+    }
+
+    #[test]
+    fn test_render_blocks() -> Result<(), AppError> {
+        let engine = TemplateEngine::new(Path::new("/tmp"), &test_variables(), &TemplateSyntax::default())?;
+        let content = "[package]\nname = \"{{ project_name }}\"\n\n[dependencies]\n\
+            {% if serde %}serde = \"1\"\n{% endif %}\
+            {% for feature in features %}{{ feature | upper }}\n{% endfor %}\
+            {{ project_name | pascal_case }}\n";
+
+        assert_eq!(
+            engine.render_str("Cargo.toml", content)?,
+            "[package]\nname = \"my-app\"\n\n[dependencies]\nserde = \"1\"\nCLI\nJSON\nMyApp\n"
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_include() -> Result<(), AppError> {
+        let template_root = Path::new("/tmp/templater_engine_template/");
+        std::fs::create_dir_all(template_root.join("partials"))?;
+        std::fs::write(template_root.join("partials/header.txt"), "// {{ project_name }}\n")?;
+
+        let engine = TemplateEngine::new(template_root, &test_variables(), &TemplateSyntax::default())?;
+
+        assert_eq!(engine.render_str("main.rs", "{% include \"partials/header.txt\" %}fn main() {}\n")?, "// my-app\nfn main() {}\n");
+
+        std::fs::remove_dir_all(template_root)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_raw_and_custom_syntax() -> Result<(), AppError> {
+        let engine = TemplateEngine::new(Path::new("/tmp"), &test_variables(), &TemplateSyntax::default())?;
+
+        assert_eq!(
+            engine.render_str("ci.yml", "name: {{ project_name }}\n{% raw %}token: ${{ secrets.TOKEN }}{% endraw %}\n")?,
+            "name: my-app\ntoken: ${{ secrets.TOKEN }}\n"
+        );
+
+        let syntax = TemplateSyntax {
+            variable_start: Some("[[".to_string()),
+            variable_end: Some("]]".to_string()),
+            block_start: Some("[%".to_string()),
+            block_end: Some("%]".to_string()),
+            ..Default::default()
+        };
+        let engine = TemplateEngine::new(Path::new("/tmp"), &test_variables(), &syntax)?;
+
+        assert_eq!(
+            engine.render_str("chart.yaml", "name: [[ project_name ]]\n[% if serde %]image: {{ .Values.image }}[% endif %]\n")?,
+            "name: my-app\nimage: {{ .Values.image }}\n"
+        );
+
+        let engine = engine.with_variable("project_name", &toml::Value::from("other"));
+        assert_eq!(engine.render_str("item.txt", "[[ project_name ]] [[ features | length ]]")?, "other 2");
+
+        let invalid = TemplateSyntax { variable_start: Some("".to_string()), ..Default::default() };
+        assert!(matches!(TemplateEngine::new(Path::new("/tmp"), &test_variables(), &invalid), Err(AppError::InvalidSyntax(_))));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_error() -> Result<(), AppError> {
+        let engine = TemplateEngine::new(Path::new("/tmp"), &test_variables(), &TemplateSyntax::default())?;
+
+        match engine.render_str("src/main.rs", "fn main() {}\n{{ unknown }}\n") {
+            Err(AppError::Render { file, line, .. }) => {
+                assert_eq!(file, "src/main.rs");
+                assert_eq!(line, 2);
+            }
+            other => panic!("Expected a render error, got {:?}", other),
+        }
+
+        match engine.render_str("README.md", "\n\n{% if project_name %}") {
+            Err(AppError::Render { file, line, .. }) => {
+                assert_eq!(file, "README.md");
+                assert_eq!(line, 3);
+            }
+            other => panic!("Expected a render error, got {:?}", other),
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_evaluate_expression() -> Result<(), AppError> {
+        let variables = test_variables();
+
+        assert_eq!(evaluate_expression("project_name | snake_case", &variables)?.as_str(), Some("my_app"));
+        assert!(evaluate_expression("serde and 'cli' in features", &variables)?.is_true());
+        assert!(!evaluate_expression("project_name == 'other'", &variables)?.is_true());
+        assert!(matches!(evaluate_expression("unknown | upper", &variables), Err(AppError::Expression { .. })));
+        assert!(matches!(evaluate_expression("project_name ==", &variables), Err(AppError::Expression { .. })));
+
+        let mut names: Vec<String> = expression_variables("crate_name | snake_case ~ suffix")?.into_iter().collect();
+        names.sort();
+        assert_eq!(names, vec!["crate_name", "suffix"]);
+
+        Ok(())
+    }
+}
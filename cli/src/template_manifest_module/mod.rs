@@ -0,0 +1,299 @@
+use crate::template_manifest_name;
+use crate::constants::TEMPLATE_MANIFEST_NAME;
+use crate::conditions_module::ConditionalPaths;
+use crate::hooks_module::TemplateHooks;
+use crate::inject_module::Injection;
+use crate::render_module::Variables;
+use crate::repeat_module::RepeatedPaths;
+use crate::scripts_module::TemplateScripts;
+use crate::template_engine_module::TemplateSyntax;
+use crate::AppError;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// The optional `template.toml` stored at the root of a template.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateManifest {
+    pub description: Option<String>,
+    pub author: Option<String>,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub variables: Vec<TemplateVariable>,
+    #[serde(default)]
+    pub conditional_paths: Vec<ConditionalPaths>,
+    #[serde(default)]
+    pub repeated_paths: Vec<RepeatedPaths>,
+    /// Globs of files that are only used through `{% include %}` and are not loaded themselves
+    #[serde(default)]
+    pub partials: Vec<String>,
+    /// Globs of files whose content is copied without rendering
+    #[serde(default)]
+    pub verbatim: Vec<String>,
+    /// Globs of TOML, JSON and YAML files that are merged into the existing files of the target directory
+    #[serde(default)]
+    pub merge: Vec<String>,
+    #[serde(default)]
+    pub syntax: TemplateSyntax,
+    /// Variables computed from the other variables, as `name = "expression"`
+    #[serde(default)]
+    pub derived: BTreeMap<String, String>,
+    #[serde(default)]
+    pub hooks: TemplateHooks,
+    #[serde(default)]
+    pub scripts: TemplateScripts,
+    #[serde(default)]
+    pub injections: Vec<Injection>,
+}
+
+/// A variable declared by a template manifest.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TemplateVariable {
+    pub name: String,
+    pub default: Option<toml::Value>,
+    pub help: Option<String>,
+    #[serde(default, rename = "type")]
+    pub variable_type: VariableType,
+    /// A regex that the whole value must match (strings and choices)
+    pub regex: Option<String>,
+    /// The smallest allowed value of an integer, or the minimum length of a string or list
+    pub min: Option<i64>,
+    /// The largest allowed value of an integer, or the maximum length of a string or list
+    pub max: Option<i64>,
+    /// The allowed values, required for the `choice` type
+    #[serde(default)]
+    pub choices: Vec<toml::Value>,
+    /// An expression over the variables declared before this one, the variable is only asked when it holds
+    pub when: Option<String>,
+}
+
+/// The type of a template variable. Values given as text, on the command line or in a prompt, are converted to it.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum VariableType {
+    #[default]
+    String,
+    Bool,
+    Integer,
+    Choice,
+    List,
+}
+
+impl std::fmt::Display for VariableType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            VariableType::String => "string",
+            VariableType::Bool => "bool",
+            VariableType::Integer => "integer",
+            VariableType::Choice => "choice",
+            VariableType::List => "list",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+impl TemplateManifest {
+    /// Parses a manifest from its TOML representation.
+    ///
+    /// # Arguments
+    ///
+    /// * `content`: &str - The content of a `template.toml` file
+    /// * `path`: &Path - The path of the manifest, used in error messages
+    ///
+    /// returns: Result<TemplateManifest, AppError>
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use std::path::Path;
+    /// use cli::template_manifest_module::TemplateManifest;
+    ///
+    /// let manifest = TemplateManifest::parse(r#"
+    /// description = "A Rust CLI"
+    ///
+    /// [[variables]]
+    /// name = "project_name"
+    /// default = "my-app"
+    /// "#, Path::new("template.toml"))?;
+    ///
+    /// assert_eq!(manifest.variables[0].name, "project_name");
+    /// ```
+    pub fn parse(content: &str, path: &Path) -> Result<Self, AppError> {
+        toml::from_str(content).map_err(|error| AppError::InvalidManifest {
+            path: path.display().to_string(),
+            error,
+        })
+    }
+
+    /// Returns the default value of every declared variable that has one.
+    pub fn default_variables(&self) -> Variables {
+        self.variables
+            .iter()
+            .filter_map(|variable| Some((variable.name.clone(), variable.default.clone()?)))
+            .collect()
+    }
+}
+
+/// Returns true if the given path, relative to the template root, is the template manifest.
+pub fn is_manifest_path(relative_path: &Path) -> bool {
+    relative_path == Path::new(template_manifest_name!())
+}
+
+/// Loads the manifest stored at the root of a template, if there is one.
+///
+/// # Arguments
+///
+/// * `template_path`: &Path - The root directory of the template
+///
+/// returns: Result<Option<TemplateManifest>, AppError>
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use std::path::Path;
+/// use cli::template_manifest_module::load_manifest;
+///
+/// match load_manifest(Path::new("/tmp/app/templater/templates/test"))? {
+///     None => println!("The template has no manifest."),
+///     Some(manifest) => println!("{:?}", manifest.description),
+/// }
+/// ```
+pub fn load_manifest(template_path: &Path) -> Result<Option<TemplateManifest>, AppError> {
+    let manifest_path = template_path.join(template_manifest_name!());
+
+    if !manifest_path.is_file() {
+        return Ok(None);
+    }
+
+    let content = std::fs::read_to_string(&manifest_path)?;
+
+    Ok(Some(TemplateManifest::parse(&content, &manifest_path)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::AppError;
+    use crate::inject_module::InjectPosition;
+    use crate::template_manifest_module::{is_manifest_path, load_manifest, TemplateManifest, VariableType};
+
+    #[test]
+    fn test_parse_manifest() -> Result<(), AppError> {
+        let manifest = TemplateManifest::parse(r#"
+            description = "A Rust CLI"
+            author = "andreishark"
+            tags = ["rust", "cli"]
+            partials = ["_partials"]
+            verbatim = [".github/workflows/*.yml"]
+            merge = ["Cargo.toml", "package.json"]
+
+            [[variables]]
+            name = "project_name"
+            default = "my-app"
+            help = "Name of the generated project"
+
+            [[variables]]
+            name = "crate_name"
+            regex = "[a-z][a-z0-9_-]*"
+
+            [[variables]]
+            name = "workers"
+            type = "integer"
+            min = 1
+            when = "project_name != 'lib'"
+
+            [[conditional_paths]]
+            paths = ["docker", "Dockerfile"]
+            variable = "docker"
+
+            [[repeated_paths]]
+            paths = ["src/handlers/*.rs"]
+            variable = "handlers"
+
+            [syntax]
+            variable_start = "[["
+            variable_end = "]]"
+
+            [derived]
+            lib_name = "crate_name | snake_case"
+
+            [hooks]
+            post_load = ["git init", "cargo generate-lockfile"]
+
+            [scripts]
+            pre_load = ["scripts/variables.rhai"]
+
+            [[injections]]
+            file = "src/lib.rs"
+            content = "mod {{ name }};"
+            marker = "// templater: modules"
+        "#, Path::new("template.toml"))?;
+
+        assert_eq!(manifest.description.as_deref(), Some("A Rust CLI"));
+        assert_eq!(manifest.author.as_deref(), Some("andreishark"));
+        assert_eq!(manifest.tags, vec!["rust", "cli"]);
+        assert_eq!(manifest.partials, vec!["_partials"]);
+        assert_eq!(manifest.verbatim, vec![".github/workflows/*.yml"]);
+        assert_eq!(manifest.merge, vec!["Cargo.toml", "package.json"]);
+        assert_eq!(manifest.syntax.variable_start.as_deref(), Some("[["));
+        assert_eq!(manifest.syntax.block_start, None);
+        assert_eq!(manifest.derived["lib_name"], "crate_name | snake_case");
+        assert_eq!(manifest.hooks.post_load, vec!["git init", "cargo generate-lockfile"]);
+        assert_eq!(manifest.scripts.pre_load, vec!["scripts/variables.rhai"]);
+        assert!(manifest.scripts.post_load.is_empty());
+        assert_eq!(manifest.injections[0].marker.as_deref(), Some("// templater: modules"));
+        assert_eq!(manifest.injections[0].position, InjectPosition::After);
+        assert_eq!(manifest.variables.len(), 3);
+        assert_eq!(manifest.variables[1].variable_type, VariableType::String);
+        assert_eq!(manifest.variables[1].regex.as_deref(), Some("[a-z][a-z0-9_-]*"));
+        assert_eq!(manifest.variables[2].variable_type, VariableType::Integer);
+        assert_eq!(manifest.variables[2].min, Some(1));
+        assert_eq!(manifest.variables[2].when.as_deref(), Some("project_name != 'lib'"));
+        assert_eq!(manifest.variables[1].when, None);
+        assert_eq!(manifest.variables[0].help.as_deref(), Some("Name of the generated project"));
+        assert_eq!(manifest.conditional_paths[0].paths, vec!["docker", "Dockerfile"]);
+        assert_eq!(manifest.repeated_paths[0].variable, "handlers");
+        assert_eq!(manifest.repeated_paths[0].item, "item");
+
+        let defaults = manifest.default_variables();
+        assert_eq!(defaults.len(), 1);
+        assert_eq!(defaults["project_name"], toml::Value::from("my-app"));
+
+        assert!(matches!(
+            TemplateManifest::parse("tags = \"rust\"", Path::new("template.toml")),
+            Err(AppError::InvalidManifest { .. })
+        ));
+        assert!(matches!(
+            TemplateManifest::parse("[scripts]\npartials = [\"_s\"]", Path::new("template.toml")),
+            Err(AppError::InvalidManifest { .. })
+        ));
+        assert!(matches!(
+            TemplateManifest::parse("[[variables]]\nname = \"ci\"\nwhen_ = \"docker\"", Path::new("template.toml")),
+            Err(AppError::InvalidManifest { .. })
+        ));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_load_manifest() -> Result<(), AppError> {
+        let template_path = Path::new("/tmp/templater_manifest_template/");
+        std::fs::create_dir_all(template_path)?;
+
+        assert_eq!(load_manifest(template_path)?, None);
+
+        std::fs::write(template_path.join("template.toml"), "description = \"test\"")?;
+        let manifest = load_manifest(template_path)?.unwrap();
+
+        assert_eq!(manifest.description.as_deref(), Some("test"));
+        assert!(is_manifest_path(Path::new("template.toml")));
+        assert!(!is_manifest_path(Path::new("src/template.toml")));
+
+        std::fs::remove_dir_all(template_path)?;
+
+        Ok(())
+    }
+}
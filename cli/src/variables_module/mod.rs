@@ -0,0 +1,180 @@
+use std::path::Path;
+use crate::render_module::Variables;
+
+/// Returns the variables every template can use without declaring them:
+/// `year`, `date`, `target_dir_name`, `template_name`, `username` (from `$USER`)
+/// and `user.name` / `user.email` (from `~/.gitconfig`).
+///
+/// # Arguments
+///
+/// * `template_name`: &str - The name of the loaded template
+/// * `target`: &Path - The directory the template is loaded into
+///
+/// returns: Variables
+///
+/// # Examples
+///
+/// ```rust,ignore
+/// use std::path::Path;
+/// use cli::variables_module::builtin_variables;
+///
+/// let variables = builtin_variables("rust-cli", Path::new("/home/user/my-app"));
+///
+/// assert_eq!(variables["target_dir_name"].as_str(), Some("my-app"));
+/// ```
+pub fn builtin_variables(template_name: &str, target: &Path) -> Variables {
+    let today = chrono::Local::now();
+    let username = std::env::var("USER").or_else(|_| std::env::var("USERNAME")).unwrap_or_default();
+
+    let gitconfig = home::home_dir()
+        .and_then(|home| std::fs::read_to_string(home.join(".gitconfig")).ok())
+        .unwrap_or_default();
+    let (git_name, git_email) = git_identity(&gitconfig);
+
+    let mut user = toml::value::Table::new();
+    user.insert("name".to_string(), toml::Value::from(git_name.unwrap_or_else(|| username.clone())));
+    user.insert("email".to_string(), toml::Value::from(git_email.unwrap_or_default()));
+
+    let target_dir_name = target.file_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+
+    let mut variables = Variables::new();
+    variables.insert("year".to_string(), toml::Value::from(i64::from(chrono::Datelike::year(&today))));
+    variables.insert("date".to_string(), toml::Value::from(today.format("%Y-%m-%d").to_string()));
+    variables.insert("target_dir_name".to_string(), toml::Value::from(target_dir_name));
+    variables.insert("template_name".to_string(), toml::Value::from(template_name));
+    variables.insert("username".to_string(), toml::Value::from(username));
+    variables.insert("user".to_string(), toml::Value::Table(user));
+    variables
+}
+
+/// Reads `user.name` and `user.email` from the content of a git config file.
+///
+/// # Arguments
+///
+/// * `gitconfig`: &str - The content of a git config file
+///
+/// returns: (Option<String>, Option<String>) - The name and the email
+pub fn git_identity(gitconfig: &str) -> (Option<String>, Option<String>) {
+    let mut in_user_section = false;
+    let mut name = None;
+    let mut email = None;
+
+    for line in gitconfig.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_user_section = line.trim_start_matches('[').trim_end_matches(']').trim().eq_ignore_ascii_case("user");
+            continue;
+        }
+
+        if !in_user_section {
+            continue;
+        }
+
+        let (key, value) = match line.split_once('=') {
+            None => continue,
+            Some((key, value)) => (key.trim(), value.trim().trim_matches('"').to_string()),
+        };
+
+        if key.eq_ignore_ascii_case("name") {
+            name = Some(value);
+        } else if key.eq_ignore_ascii_case("email") {
+            email = Some(value);
+        }
+    }
+
+    (name, email)
+}
+
+/// Sets a variable, where a dotted key such as `user.name` sets a value inside a table.
+///
+/// # Arguments
+///
+/// * `variables`: &mut Variables - The variables to change
+/// * `key`: &str - The name of the variable, parts separated by `.`
+/// * `value`: toml::Value - The new value
+pub fn set_variable(variables: &mut Variables, key: &str, value: toml::Value) {
+    let (first, rest) = match key.split_once('.') {
+        None => {
+            variables.insert(key.to_string(), value);
+            return;
+        }
+        Some(parts) => parts,
+    };
+
+    let mut nested: Variables = match variables.remove(first) {
+        Some(toml::Value::Table(table)) => table.into_iter().collect(),
+        _ => Variables::new(),
+    };
+    set_variable(&mut nested, rest, value);
+
+    variables.insert(first.to_string(), toml::Value::Table(nested.into_iter().collect()));
+}
+
+/// Merges the overrides into the variables. Tables are merged key by key, every other value is replaced.
+///
+/// # Arguments
+///
+/// * `variables`: &mut Variables - The variables to change
+/// * `overrides`: Variables - The values that take priority
+pub fn merge_variables(variables: &mut Variables, overrides: Variables) {
+    for (key, value) in overrides {
+        match (variables.get_mut(&key), value) {
+            (Some(toml::Value::Table(existing)), toml::Value::Table(table)) => {
+                let mut nested: Variables = std::mem::take(existing).into_iter().collect();
+                merge_variables(&mut nested, table.into_iter().collect());
+                *existing = nested.into_iter().collect();
+            }
+            (_, value) => {
+                variables.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+    use crate::render_module::Variables;
+    use crate::variables_module::{builtin_variables, git_identity, merge_variables, set_variable};
+
+    #[test]
+    fn test_builtin_variables() {
+        let variables = builtin_variables("rust-cli", Path::new("/tmp/my-app"));
+
+        assert_eq!(variables["template_name"].as_str(), Some("rust-cli"));
+        assert_eq!(variables["target_dir_name"].as_str(), Some("my-app"));
+        assert!(variables["year"].as_integer().unwrap() >= 2023);
+        assert_eq!(variables["date"].as_str().unwrap().len(), 10);
+        assert!(variables["user"].get("name").is_some());
+        assert!(variables["user"].get("email").is_some());
+    }
+
+    #[test]
+    fn test_git_identity() {
+        let gitconfig = "[core]\n\tname = not-me\n[user]\n\tname = Andrei Shark\n\temail = \"andrei@example.com\"\n[alias]\n\tst = status\n";
+
+        assert_eq!(
+            git_identity(gitconfig),
+            (Some("Andrei Shark".to_string()), Some("andrei@example.com".to_string()))
+        );
+        assert_eq!(git_identity(""), (None, None));
+    }
+
+    #[test]
+    fn test_set_and_merge_variables() {
+        let mut variables = builtin_variables("rust-cli", Path::new("/tmp/my-app"));
+
+        set_variable(&mut variables, "user.name", toml::Value::from("Someone Else"));
+        set_variable(&mut variables, "year", toml::Value::from("1999"));
+
+        assert_eq!(variables["user"]["name"].as_str(), Some("Someone Else"));
+        assert!(variables["user"].get("email").is_some());
+        assert_eq!(variables["year"].as_str(), Some("1999"));
+
+        let mut overrides = Variables::new();
+        set_variable(&mut overrides, "user.email", toml::Value::from("someone@example.com"));
+        merge_variables(&mut variables, overrides);
+
+        assert_eq!(variables["user"]["name"].as_str(), Some("Someone Else"));
+        assert_eq!(variables["user"]["email"].as_str(), Some("someone@example.com"));
+    }
+}